@@ -0,0 +1,96 @@
+use std::fs::OpenOptions;
+use std::hint::black_box;
+use std::io::Write;
+use std::time::Instant;
+
+use todo::color::{disable_colors, ConsoleBackgroundColors, ConsoleForegroundColors};
+use todo::console::{get_tagged_text, Console};
+use todo::{get_type_from_string, Task, TodoList};
+
+// a long list, each frame draws every line of it
+const TASKS: usize = 1000;
+const FRAMES: u32 = 500;
+
+fn time(name: &str, mut frame: impl FnMut()) {
+    for _ in 0..FRAMES / 10 {
+        frame();
+    }
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        frame();
+    }
+    println!("{:<36} {:>10.2?} per frame", name, start.elapsed() / FRAMES);
+}
+
+fn main() {
+    let lines: Vec<String> = (0..TASKS)
+        .map(|i| format!("[ ] task number {} #home due:2026-01-01", i))
+        .collect();
+    let color_lines = || {
+        for line in &lines {
+            black_box(get_tagged_text(
+                ConsoleForegroundColors::Blue,
+                ConsoleBackgroundColors::None,
+                black_box(line),
+                ConsoleForegroundColors::Cyan,
+            ));
+        }
+    };
+
+    time("colored lines", color_lines);
+    write_frames(&lines);
+    // colors stay off for the rest of the run
+    disable_colors();
+    // the uncolored line used to be copied into a new String like this
+    time("uncolored lines, copied", || {
+        for line in &lines {
+            black_box(
+                get_tagged_text(
+                    ConsoleForegroundColors::Blue,
+                    ConsoleBackgroundColors::None,
+                    black_box(line),
+                    ConsoleForegroundColors::Cyan,
+                )
+                .into_owned(),
+            );
+        }
+    });
+    time("uncolored lines, borrowed", color_lines);
+}
+
+// the frame goes to /dev/null, so only the cost of the writes themselves is left
+fn write_frames(lines: &[String]) {
+    let mut todo_list = TodoList::new();
+    for line in lines {
+        todo_list
+            .tasks
+            .push(Task::new(line, get_type_from_string(line)));
+    }
+    let mut console = Console::new();
+    console.terminal_size = (200, 200);
+    let mut output = OpenOptions::new().write(true).open("/dev/null").unwrap();
+
+    time("render", || {
+        black_box(todo_list.render(&mut console));
+    });
+    let frame = todo_list.render(&mut console);
+    // every row starts by moving the cursor to its first column
+    let mut starts = vec![0];
+    starts.extend(
+        frame
+            .match_indices(";1H")
+            .map(|(end, _)| frame[..end].rfind("\x1b[").unwrap()),
+    );
+    starts.push(frame.len());
+    // one write per row, as the rows were printed before
+    time("write row by row", || {
+        for row in starts.windows(2) {
+            output.write_all(&frame.as_bytes()[row[0]..row[1]]).unwrap();
+            output.flush().unwrap();
+        }
+    });
+    time("write the frame at once", || {
+        output.write_all(frame.as_bytes()).unwrap();
+        output.flush().unwrap();
+    });
+}
@@ -0,0 +1,236 @@
+use std::borrow::Cow;
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::task::{type_to_string, TaskType};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ConsoleForegroundColors {
+    Black = 30,
+    Red = 31,
+    Green = 32,
+    Yellow = 33,
+    Blue = 34,
+    Magenta = 35,
+    Cyan = 36,
+    White = 37,
+    BrightBlack = 90,
+    BrightRed = 91,
+    BrightGreen = 92,
+    BrightYellow = 93,
+    BrightBlue = 94,
+    BrightMagenta = 95,
+    BrightCyan = 96,
+    BrightWhite = 97,
+}
+
+impl ConsoleForegroundColors {
+    pub fn bright(self) -> ConsoleForegroundColors {
+        match self {
+            ConsoleForegroundColors::Black => ConsoleForegroundColors::BrightBlack,
+            ConsoleForegroundColors::Red => ConsoleForegroundColors::BrightRed,
+            ConsoleForegroundColors::Green => ConsoleForegroundColors::BrightGreen,
+            ConsoleForegroundColors::Yellow => ConsoleForegroundColors::BrightYellow,
+            ConsoleForegroundColors::Blue => ConsoleForegroundColors::BrightBlue,
+            ConsoleForegroundColors::Magenta => ConsoleForegroundColors::BrightMagenta,
+            ConsoleForegroundColors::Cyan => ConsoleForegroundColors::BrightCyan,
+            ConsoleForegroundColors::White => ConsoleForegroundColors::BrightWhite,
+            bright => bright,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum ConsoleBackgroundColors {
+    None = 0,
+    Black = 40,
+    Red = 41,
+    Green = 42,
+    Yellow = 43,
+    Blue = 44,
+    Magenta = 45,
+    Cyan = 46,
+    White = 47,
+}
+
+// turned off once at startup by --no-color or the NO_COLOR variable
+static COLORS_ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub fn colors_enabled() -> bool {
+    COLORS_ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn disable_colors() {
+    COLORS_ENABLED.store(false, Ordering::Relaxed);
+}
+
+// the text is only copied when escape codes have to be added around it
+pub fn get_color_text(
+    color: ConsoleForegroundColors,
+    background_color: ConsoleBackgroundColors,
+    text: &str,
+) -> Cow<'_, str> {
+    if !colors_enabled() {
+        Cow::Borrowed(text)
+    } else if background_color == ConsoleBackgroundColors::None {
+        Cow::Owned(format!("\x1b[{}m{}\x1b[0m", color as u8, text))
+    } else {
+        Cow::Owned(format!(
+            "\x1b[{};{}m{}\x1b[0m",
+            color as u8, background_color as u8, text
+        ))
+    }
+}
+
+pub fn get_color_from_string(name: &str) -> Option<ConsoleForegroundColors> {
+    match name.trim().to_lowercase().as_str() {
+        "black" => Some(ConsoleForegroundColors::Black),
+        "red" => Some(ConsoleForegroundColors::Red),
+        "green" => Some(ConsoleForegroundColors::Green),
+        "yellow" => Some(ConsoleForegroundColors::Yellow),
+        "blue" => Some(ConsoleForegroundColors::Blue),
+        "magenta" => Some(ConsoleForegroundColors::Magenta),
+        "cyan" => Some(ConsoleForegroundColors::Cyan),
+        "white" => Some(ConsoleForegroundColors::White),
+        "bright-black" | "gray" | "grey" => Some(ConsoleForegroundColors::BrightBlack),
+        "bright-red" => Some(ConsoleForegroundColors::BrightRed),
+        "bright-green" => Some(ConsoleForegroundColors::BrightGreen),
+        "bright-yellow" => Some(ConsoleForegroundColors::BrightYellow),
+        "bright-blue" => Some(ConsoleForegroundColors::BrightBlue),
+        "bright-magenta" => Some(ConsoleForegroundColors::BrightMagenta),
+        "bright-cyan" => Some(ConsoleForegroundColors::BrightCyan),
+        "bright-white" => Some(ConsoleForegroundColors::BrightWhite),
+        _ => None,
+    }
+}
+
+// inverse of get_color_from_string
+pub fn color_to_string(color: ConsoleForegroundColors) -> &'static str {
+    match color {
+        ConsoleForegroundColors::Black => "black",
+        ConsoleForegroundColors::Red => "red",
+        ConsoleForegroundColors::Green => "green",
+        ConsoleForegroundColors::Yellow => "yellow",
+        ConsoleForegroundColors::Blue => "blue",
+        ConsoleForegroundColors::Magenta => "magenta",
+        ConsoleForegroundColors::Cyan => "cyan",
+        ConsoleForegroundColors::White => "white",
+        ConsoleForegroundColors::BrightBlack => "bright-black",
+        ConsoleForegroundColors::BrightRed => "bright-red",
+        ConsoleForegroundColors::BrightGreen => "bright-green",
+        ConsoleForegroundColors::BrightYellow => "bright-yellow",
+        ConsoleForegroundColors::BrightBlue => "bright-blue",
+        ConsoleForegroundColors::BrightMagenta => "bright-magenta",
+        ConsoleForegroundColors::BrightCyan => "bright-cyan",
+        ConsoleForegroundColors::BrightWhite => "bright-white",
+    }
+}
+
+// colors a task can be flagged with, in the order c goes through them before going back
+// to the color of the status
+const OVERRIDE_COLORS: [ConsoleForegroundColors; 6] = [
+    ConsoleForegroundColors::Red,
+    ConsoleForegroundColors::Yellow,
+    ConsoleForegroundColors::Green,
+    ConsoleForegroundColors::Cyan,
+    ConsoleForegroundColors::Blue,
+    ConsoleForegroundColors::Magenta,
+];
+
+pub fn next_override_color(
+    color: Option<ConsoleForegroundColors>,
+) -> Option<ConsoleForegroundColors> {
+    match color {
+        None => Some(OVERRIDE_COLORS[0]),
+        // a color set by hand in the file that is not in the cycle ends it
+        Some(color) => OVERRIDE_COLORS
+            .iter()
+            .position(|&own| own == color)
+            .and_then(|position| OVERRIDE_COLORS.get(position + 1))
+            .copied(),
+    }
+}
+
+// color of each status, and the glyph drawn in place of its marker
+pub struct Theme {
+    pub todo: ConsoleForegroundColors,
+    pub doing: ConsoleForegroundColors,
+    pub done: ConsoleForegroundColors,
+    pub rejected: ConsoleForegroundColors,
+    pub tag: ConsoleForegroundColors,
+    pub todo_glyph: String,
+    pub doing_glyph: String,
+    pub done_glyph: String,
+    pub rejected_glyph: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Theme {
+    pub fn new() -> Self {
+        Self {
+            todo: ConsoleForegroundColors::Blue,
+            doing: ConsoleForegroundColors::Magenta,
+            done: ConsoleForegroundColors::Green,
+            rejected: ConsoleForegroundColors::Red,
+            tag: ConsoleForegroundColors::Yellow,
+            todo_glyph: type_to_string(TaskType::Todo),
+            doing_glyph: type_to_string(TaskType::Doing),
+            done_glyph: type_to_string(TaskType::Done),
+            rejected_glyph: type_to_string(TaskType::Rejected),
+        }
+    }
+
+    // override the theme with TODO_COLOR_<STATUS> and TODO_GLYPH_<STATUS>, unset or
+    // unknown colors and empty glyphs are ignored
+    pub fn with_env(self) -> Self {
+        let mut theme = self;
+        let color = |name: &str, default: ConsoleForegroundColors| {
+            env::var(name)
+                .ok()
+                .and_then(|value| get_color_from_string(&value))
+                .unwrap_or(default)
+        };
+        theme.todo = color("TODO_COLOR_TODO", theme.todo);
+        theme.doing = color("TODO_COLOR_DOING", theme.doing);
+        theme.done = color("TODO_COLOR_DONE", theme.done);
+        theme.rejected = color("TODO_COLOR_REJECTED", theme.rejected);
+        theme.tag = color("TODO_COLOR_TAG", theme.tag);
+        let glyph = |name: &str, default: String| {
+            env::var(name)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .unwrap_or(default)
+        };
+        theme.todo_glyph = glyph("TODO_GLYPH_TODO", theme.todo_glyph);
+        theme.doing_glyph = glyph("TODO_GLYPH_DOING", theme.doing_glyph);
+        theme.done_glyph = glyph("TODO_GLYPH_DONE", theme.done_glyph);
+        theme.rejected_glyph = glyph("TODO_GLYPH_REJECTED", theme.rejected_glyph);
+        theme
+    }
+
+    pub fn color(&self, task_type: TaskType) -> ConsoleForegroundColors {
+        match task_type {
+            TaskType::Todo => self.todo,
+            TaskType::Doing => self.doing,
+            TaskType::Done => self.done,
+            TaskType::Rejected => self.rejected,
+            TaskType::NotDefined => ConsoleForegroundColors::White,
+        }
+    }
+
+    // shown instead of the stored marker, which stays the same in the file
+    pub fn glyph(&self, task_type: TaskType) -> &str {
+        match task_type {
+            TaskType::Todo => &self.todo_glyph,
+            TaskType::Doing => &self.doing_glyph,
+            TaskType::Done => &self.done_glyph,
+            TaskType::Rejected => &self.rejected_glyph,
+            TaskType::NotDefined => "",
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::color::{get_color_from_string, Theme};
+use crate::keymap::{key_name, parse_key, Action, KeyMap};
+use crate::task::{parse_status_cycle, TaskType};
+use crate::todo_list::SortMode;
+
+// seconds between saves of changed lists, 0 turns saving in the background off
+pub const AUTOSAVE_SECONDS: u64 = 30;
+
+// settings from the config file, the environment variables and the command line flags
+// are applied over them
+pub struct Config {
+    pub theme: Theme,
+    pub sort_mode: SortMode,
+    pub autosave_seconds: u64,
+    // None keeps the default order of the statuses
+    pub cycle: Option<Vec<TaskType>>,
+    pub keymap: KeyMap,
+    // lines of the file that were not understood, they are skipped and reported at startup
+    pub warnings: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: Theme::new(),
+            sort_mode: SortMode::Status,
+            autosave_seconds: AUTOSAVE_SECONDS,
+            cycle: None,
+            keymap: KeyMap::default(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+// $XDG_CONFIG_HOME/todo-rust/config.toml, ~/.config/todo-rust/config.toml without it
+pub fn config_path() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME").filter(|home| !home.is_empty())?).join(".config"),
+    };
+    Some(dir.join("todo-rust").join("config.toml"))
+}
+
+impl Config {
+    // the defaults when there is no config file, a file that cannot be read is a warning
+    pub fn load() -> Config {
+        let Some(path) = config_path() else {
+            return Config::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => Config::parse(&content),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Config::default(),
+            Err(error) => {
+                let mut config = Config::default();
+                config
+                    .warnings
+                    .push(format!("Could not read {}: {}", path.display(), error));
+                config
+            }
+        }
+    }
+
+    // a small part of TOML, enough for
+    //
+    //   sort = "status"        # status, open, text or insertion
+    //   autosave = 30
+    //   cycle = "todo,doing,done"
+    //   [colors]
+    //   done = "cyan"
+    //   [glyphs]
+    //   done = "✓"
+    //   [keys]
+    //   cycle-status = "space l"
+    //
+    // anything left out keeps its default
+    pub fn parse(content: &str) -> Config {
+        let mut config = Config::default();
+        let mut section = String::new();
+        for (number, line) in content.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                section = name.trim().to_string();
+                continue;
+            }
+            let warning = match line.split_once('=') {
+                Some((key, value)) => config.set(&section, key.trim(), value.trim()),
+                None => Err("expected key = value".to_string()),
+            };
+            if let Err(warning) = warning {
+                config
+                    .warnings
+                    .push(format!("config.toml line {}: {}", number + 1, warning));
+            }
+        }
+        config
+    }
+
+    fn set(&mut self, section: &str, key: &str, value: &str) -> Result<(), String> {
+        let invalid = || format!("invalid value {} for {}", value, key);
+        match (section, key) {
+            ("", "sort") => {
+                self.sort_mode = SortMode::from_name(&parse_string(value)?).ok_or_else(invalid)?
+            }
+            ("", "autosave") => self.autosave_seconds = value.parse().map_err(|_| invalid())?,
+            ("", "cycle") => {
+                self.cycle = Some(parse_status_cycle(&parse_string(value)?).ok_or_else(invalid)?)
+            }
+            ("colors", name) => {
+                let color = get_color_from_string(&parse_string(value)?).ok_or_else(invalid)?;
+                match name {
+                    "todo" => self.theme.todo = color,
+                    "doing" => self.theme.doing = color,
+                    "done" => self.theme.done = color,
+                    "rejected" => self.theme.rejected = color,
+                    "tag" => self.theme.tag = color,
+                    _ => return Err(format!("unknown color {}", name)),
+                }
+            }
+            ("glyphs", name) => {
+                let glyph = parse_string(value)?;
+                if glyph.is_empty() {
+                    return Err(invalid());
+                }
+                match TaskType::from_keyword(name) {
+                    Some(TaskType::Todo) => self.theme.todo_glyph = glyph,
+                    Some(TaskType::Doing) => self.theme.doing_glyph = glyph,
+                    Some(TaskType::Done) => self.theme.done_glyph = glyph,
+                    Some(TaskType::Rejected) => self.theme.rejected_glyph = glyph,
+                    _ => return Err(format!("unknown glyph {}", name)),
+                }
+            }
+            // the keys replace the default ones of the action, taking a key from another
+            // action is allowed but warned about
+            ("keys", name) => {
+                let action =
+                    Action::from_name(name).ok_or_else(|| format!("unknown action {}", name))?;
+                let keys = parse_string(value)?
+                    .split_whitespace()
+                    .map(|key| parse_key(key).ok_or_else(|| format!("unknown key {}", key)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let taken: Vec<String> = self
+                    .keymap
+                    .rebind(action, &keys)
+                    .into_iter()
+                    .map(|(key, previous)| {
+                        format!("{} no longer does {}", key_name(key), previous.name())
+                    })
+                    .collect();
+                if !taken.is_empty() {
+                    return Err(taken.join(", "));
+                }
+            }
+            _ if section.is_empty() => return Err(format!("unknown setting {}", key)),
+            _ => return Err(format!("unknown setting {}.{}", section, key)),
+        }
+        Ok(())
+    }
+}
+
+// a # outside of a string starts a comment
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+// a basic TOML string, "..." with \" \\ \n and \t escapes
+fn parse_string(value: &str) -> Result<String, String> {
+    let inner = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .filter(|_| value.len() >= 2)
+        .ok_or_else(|| format!("expected a quoted string, found {}", value))?;
+    let mut text = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('"') => text.push('"'),
+            Some('\\') => text.push('\\'),
+            Some('n') => text.push('\n'),
+            Some('t') => text.push('\t'),
+            _ => return Err(format!("invalid escape in {}", value)),
+        }
+    }
+    Ok(text)
+}
@@ -0,0 +1,1163 @@
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::io::{self, stdin, stdout, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use termion::event::{Event, Key};
+use termion::input::TermRead;
+use termion::style;
+
+use crate::color::{
+    colors_enabled, get_color_text, ConsoleBackgroundColors, ConsoleForegroundColors, Theme,
+};
+use crate::date::Date;
+use crate::keymap::KeyMap;
+use crate::task::{get_display_text, get_tag, Task, TaskType, INDENT};
+use crate::todo_list::{Direction, TaskCounts, TodoList};
+
+pub struct Console {
+    pub cursor_position: (u16, u16),
+    pub header: Option<String>,
+    // show the cursor coordinates above the status bar
+    pub debug: bool,
+    // last known (width, height) of the terminal
+    pub terminal_size: (u16, u16),
+    pub theme: Theme,
+    // shown on the status bar until the next keypress
+    pub message: Option<String>,
+    // like message but in green, for confirmations such as a successful save
+    pub notice: Option<String>,
+    // nothing can be changed and nothing is saved
+    pub read_only: bool,
+    // number of view rows scrolled off the top of the screen
+    pub scroll_offset: usize,
+    // row where the visual selection started, it runs from there to the cursor
+    pub selection_anchor: Option<u16>,
+    // characters of the selected task scrolled off the left edge, None when Left and Right
+    // do not scroll
+    pub horizontal_scroll: Option<usize>,
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Self {
+            cursor_position: (1, 1),
+            header: None,
+            debug: false,
+            terminal_size: termion::terminal_size().unwrap_or((80, 24)),
+            theme: Theme::new(),
+            message: None,
+            notice: None,
+            read_only: false,
+            scroll_offset: 0,
+            selection_anchor: None,
+            horizontal_scroll: None,
+        }
+    }
+
+    // first and last row of the visual selection, whichever way it was made
+    pub fn selection(&self) -> Option<(u16, u16)> {
+        let anchor = self.selection_anchor?;
+        let row = self.cursor_position.1;
+        Some((anchor.min(row), anchor.max(row)))
+    }
+
+    // whether the row is highlighted, i.e. under the cursor or in the visual selection
+    pub fn is_highlighted(&self, row: u16) -> bool {
+        match self.selection() {
+            Some((first, last)) => (first..=last).contains(&row),
+            None => self.cursor_position.1 == row,
+        }
+    }
+
+    // whether the terminal size differs from the last known one, without updating it
+    pub fn is_resized(&self) -> bool {
+        termion::terminal_size().is_ok_and(|size| size != self.terminal_size)
+    }
+
+    // query the terminal size, returns true when it changed since the last call
+    pub fn refresh_terminal_size(&mut self) -> bool {
+        let size = termion::terminal_size().unwrap_or(self.terminal_size);
+        let changed = size != self.terminal_size;
+        self.terminal_size = size;
+        changed
+    }
+
+    // screen row of the first task, the header takes the first row when shown
+    pub fn list_top(&self) -> u16 {
+        if self.header.is_some() {
+            2
+        } else {
+            1
+        }
+    }
+
+    // rows available for tasks, the last two are kept for the prompt and the status bar
+    pub fn visible_rows(&self) -> usize {
+        let (_, height) = self.terminal_size;
+        (height as usize + 1)
+            .saturating_sub(self.list_top() as usize + 2)
+            .max(1)
+    }
+
+    // screen row of a 1-based view row, taking the scrolling into account
+    pub fn screen_row(&self, row: u16) -> u16 {
+        (row as usize + self.list_top() as usize).saturating_sub(self.scroll_offset + 1) as u16
+    }
+
+    // view row shown on a screen row, inverse of screen_row, None outside the list
+    pub fn view_row(&self, screen_row: u16, tasks_count: usize) -> Option<u16> {
+        let offset = (screen_row as usize).checked_sub(self.list_top() as usize)?;
+        if offset >= self.visible_rows() {
+            return None;
+        }
+        let row = self.scroll_offset + offset + 1;
+        (row <= tasks_count).then_some(row as u16)
+    }
+
+    // scroll just enough to keep the cursor on screen
+    pub fn scroll_to_cursor(&mut self, tasks_count: usize) {
+        let visible = self.visible_rows();
+        let row = self.cursor_position.1 as usize;
+        self.scroll_offset = self.scroll_offset.min(tasks_count.saturating_sub(visible));
+        if row <= self.scroll_offset {
+            self.scroll_offset = row.saturating_sub(1);
+        } else if row > self.scroll_offset + visible {
+            self.scroll_offset = row - visible;
+        }
+    }
+
+    // move the list and the cursor a screen up or down together, clamped at the first and
+    // the last page
+    pub fn page(&mut self, direction: Direction, tasks_count: usize) {
+        let visible = self.visible_rows();
+        let row = self.cursor_position.1 as usize;
+        match direction {
+            Direction::Up => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(visible);
+                self.cursor_position.1 = row.saturating_sub(visible).max(1) as u16;
+            }
+            _ => {
+                self.scroll_offset =
+                    (self.scroll_offset + visible).min(tasks_count.saturating_sub(visible));
+                self.cursor_position.1 = (row + visible).min(tasks_count).max(1) as u16;
+            }
+        }
+    }
+
+    // the page of the task at the 1-based position among the tasks and the number of pages,
+    // e.g. (2, 4), headers are not counted
+    pub fn page_position(&self, position: usize, tasks_count: usize) -> (usize, usize) {
+        let visible = self.visible_rows();
+        let page = position.saturating_sub(1) / visible + 1;
+        let pages = tasks_count.div_ceil(visible).max(1);
+        (page.min(pages), pages)
+    }
+
+    // scroll the selected task by a number of characters, the render keeps its end on screen
+    pub fn scroll_horizontally(&mut self, direction: Direction) {
+        if let Some(offset) = &mut self.horizontal_scroll {
+            *offset = match direction {
+                Direction::Left => offset.saturating_sub(HORIZONTAL_SCROLL_STEP),
+                _ => *offset + HORIZONTAL_SCROLL_STEP,
+            };
+        }
+    }
+
+    pub fn clamp_cursor(&mut self, tasks_count: usize) {
+        if self.cursor_position.1 as usize > tasks_count {
+            self.cursor_position.1 = tasks_count.max(1) as u16;
+        }
+    }
+
+    // move the cursor to the task with the 1-based number typed in the jump prompt, counted
+    // among the task rows so headers are skipped; numbers past either end go to the first or
+    // the last task, returns false when the input is not a number
+    pub fn jump_to(&mut self, input: &str, task_rows: &[u16]) -> bool {
+        let Ok(number) = input.trim().parse::<usize>() else {
+            return false;
+        };
+        self.cursor_position.1 = match task_rows.len() {
+            0 => 1,
+            count => task_rows[number.clamp(1, count) - 1],
+        };
+        true
+    }
+}
+
+// terminal events read on their own thread, so waiting for a key can time out
+// how often the main loop checks the terminal size while no key is pressed
+pub const RESIZE_POLL: Duration = Duration::from_millis(250);
+
+pub struct InputEvents {
+    receiver: Receiver<io::Result<Event>>,
+}
+
+impl InputEvents {
+    pub fn from_stdin() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for event in stdin().events() {
+                if sender.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { receiver }
+    }
+
+    // wait at most the timeout for the next event
+    pub fn next_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<io::Result<Event>, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+}
+
+impl Iterator for InputEvents {
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+// color a line, with the #tags in it drawn in their own color
+pub fn get_tagged_text(
+    color: ConsoleForegroundColors,
+    background_color: ConsoleBackgroundColors,
+    text: &str,
+    tag_color: ConsoleForegroundColors,
+) -> Cow<'_, str> {
+    if !colors_enabled() {
+        return Cow::Borrowed(text);
+    }
+    // the text between the tags is colored as one run, only the tags get their own color
+    let mut colored = String::with_capacity(text.len() + 16);
+    let mut run_start = 0;
+    let mut word_start = 0;
+    for word in text.split(' ') {
+        if get_tag(word).is_some() {
+            if run_start < word_start {
+                colored.push_str(&get_color_text(
+                    color,
+                    background_color,
+                    &text[run_start..word_start],
+                ));
+            }
+            colored.push_str(&get_color_text(tag_color, background_color, word));
+            run_start = word_start + word.len();
+        }
+        word_start += word.len() + 1;
+    }
+    if run_start < text.len() {
+        colored.push_str(&get_color_text(color, background_color, &text[run_start..]));
+    }
+    Cow::Owned(colored)
+}
+
+// byte ranges of the case-insensitive occurrences of the query in the text
+pub fn find_matches(text: &str, query: &str) -> Vec<(usize, usize)> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let mut matches = Vec::new();
+    if query.is_empty() {
+        return matches;
+    }
+    let mut start = 0;
+    while let Some(c) = text[start..].chars().next() {
+        match prefix_match(&text[start..], &query) {
+            Some(length) => {
+                matches.push((start, start + length));
+                start += length;
+            }
+            None => start += c.len_utf8(),
+        }
+    }
+    matches
+}
+
+// length in bytes of the start of the text that lowercases to the query
+fn prefix_match(text: &str, query: &[char]) -> Option<usize> {
+    let mut matched = 0;
+    for (offset, c) in text.char_indices() {
+        for lower in c.to_lowercase() {
+            if query.get(matched) != Some(&lower) {
+                return None;
+            }
+            matched += 1;
+        }
+        if matched == query.len() {
+            return Some(offset + c.len_utf8());
+        }
+    }
+    None
+}
+
+// color a line like get_tagged_text, with the occurrences of the query within the byte span
+// standing out
+pub fn get_highlighted_text<'a>(
+    color: ConsoleForegroundColors,
+    background_color: ConsoleBackgroundColors,
+    text: &'a str,
+    tag_color: ConsoleForegroundColors,
+    query: &str,
+    span: Range<usize>,
+) -> Cow<'a, str> {
+    let matches: Vec<(usize, usize)> = find_matches(&text[span.clone()], query)
+        .into_iter()
+        .map(|(start, end)| (start + span.start, end + span.start))
+        .collect();
+    if matches.is_empty() || !colors_enabled() {
+        return get_tagged_text(color, background_color, text, tag_color);
+    }
+    let mut highlighted = String::new();
+    let mut position = 0;
+    for (start, end) in matches {
+        highlighted.push_str(&get_tagged_text(
+            color,
+            background_color,
+            &text[position..start],
+            tag_color,
+        ));
+        highlighted.push_str(&get_color_text(
+            ConsoleForegroundColors::Black,
+            ConsoleBackgroundColors::Yellow,
+            &text[start..end],
+        ));
+        position = end;
+    }
+    highlighted.push_str(&get_tagged_text(
+        color,
+        background_color,
+        &text[position..],
+        tag_color,
+    ));
+    Cow::Owned(highlighted)
+}
+
+// a task as drawn in the list, with its glyph and indentation and without the dates
+fn display_line(task: &Task, theme: &Theme) -> String {
+    let mut text = match task.task_type {
+        TaskType::NotDefined => get_display_text(&task.text),
+        task_type => format!(
+            "{} {}",
+            theme.glyph(task_type),
+            get_display_text(task.display_text())
+        )
+        .trim_end()
+        .to_string(),
+    };
+    if !task.notes.is_empty() {
+        text.push_str(" …");
+    }
+    text.insert_str(0, &INDENT.repeat(task.depth));
+    text
+}
+
+// the characters of display_line that show the description, the only part the search
+// highlights
+fn description_range(task: &Task, theme: &Theme) -> Range<usize> {
+    let (prefix, description) = match task.task_type {
+        TaskType::NotDefined => (0, get_display_text(&task.text)),
+        task_type => (
+            theme.glyph(task_type).chars().count() + 1,
+            get_display_text(task.display_text()),
+        ),
+    };
+    let start = INDENT.len() * task.depth + prefix;
+    start..start + description.chars().count()
+}
+
+// headers are drawn in bold so they stand apart from the tasks
+fn header_text(text: &str) -> String {
+    if colors_enabled() {
+        format!("{}{}{}", style::Bold, text, style::Reset)
+    } else {
+        text.to_string()
+    }
+}
+
+// characters Left and Right scroll a long task by
+pub const HORIZONTAL_SCROLL_STEP: usize = 8;
+
+// the part of a line that fits the width starting at the offset, a < or > takes the place
+// of the first or last character when there is more text on that side
+pub fn fit_line(text: &str, offset: usize, width: usize) -> String {
+    let length = text.chars().count();
+    if length <= width && offset == 0 {
+        return text.to_string();
+    }
+    let offset = offset.min(length.saturating_sub(width));
+    let mut line: String = text.chars().skip(offset).collect();
+    if offset > 0 {
+        line.replace_range(..line.chars().next().map_or(0, char::len_utf8), "<");
+    }
+    if length - offset > width {
+        line = line.chars().take(width.saturating_sub(1)).collect();
+        line.push('>');
+    }
+    line
+}
+
+// lines without a status marker are drawn in white, so they can be found and fixed
+fn task_color(task: &Task, theme: &Theme) -> ConsoleForegroundColors {
+    if let Some(color) = task.color_override {
+        return color;
+    }
+    match task.task_type {
+        TaskType::NotDefined => theme.color(task.task_type),
+        task_type => task.priority.color(theme.color(task_type)),
+    }
+}
+
+// drawing the list lives with the rest of the terminal code
+impl TodoList {
+    // the whole screen is built first and written at once, so a redraw does not flicker
+    pub fn print(&mut self, console: &mut Console) {
+        let frame = self.render(console);
+        let mut stdout = stdout();
+        write!(stdout, "{}", frame).unwrap();
+        stdout.flush().unwrap();
+    }
+
+    // the shown tasks one per line, colored but without the cursor, header or status bar
+    pub fn to_colored_text(&self, theme: &Theme) -> String {
+        self.view_order()
+            .into_iter()
+            .map(|index| {
+                let task = &self.tasks[index];
+                if task.is_header() {
+                    return format!("{}\n", header_text(&task.text));
+                }
+                let text = display_line(task, theme);
+                format!(
+                    "{}\n",
+                    get_tagged_text(
+                        task_color(task, theme),
+                        ConsoleBackgroundColors::None,
+                        &text,
+                        theme.tag
+                    )
+                )
+            })
+            .collect()
+    }
+
+    // Up and Down: move the cursor to the next task row, headers are skipped and the ends of
+    // the list stop it; a scrolled task starts from its beginning again
+    pub fn step_cursor(&self, console: &mut Console, direction: Direction) {
+        if let Some(row) = self.next_task_row(console.cursor_position.1, direction) {
+            console.cursor_position.1 = row;
+        }
+        console.horizontal_scroll = console.horizontal_scroll.map(|_| 0);
+    }
+
+    // Right: cycle the status of the task under the cursor and follow it in case the new
+    // status moved it in the view, returns false when the cursor is not on a task
+    pub fn cycle_at_cursor(&mut self, console: &mut Console) -> bool {
+        let Some(index) = self.task_index(console.cursor_position.1) else {
+            return false;
+        };
+        self.change_type(index);
+        console.cursor_position.1 = self.task_row(index);
+        self.is_editing = true;
+        true
+    }
+
+    // the escape codes and text that draw the list, the header and the status bar
+    pub fn render(&mut self, console: &mut Console) -> String {
+        let mut frame = String::new();
+        let view_order = self.view_order();
+        let today = Date::today();
+        // a resize can leave text anywhere on the screen, so start from a blank one
+        if console.refresh_terminal_size() {
+            write!(frame, "{}", termion::clear::All).unwrap();
+        }
+        // a cursor left past the end, e.g. by a shorter filtered list, goes to the last row;
+        // the column is not used by the list and only has to stay on screen
+        console.clamp_cursor(view_order.len());
+        console.cursor_position.0 = console
+            .cursor_position
+            .0
+            .clamp(1, console.terminal_size.0.max(1));
+        // the cursor never rests on a header, it lands on the task below or above it
+        let row = console.cursor_position.1;
+        if view_order.len() >= row as usize && self.task_index(row).is_none() {
+            if let Some(row) = self
+                .next_task_row(row, Direction::Down)
+                .or_else(|| self.next_task_row(row, Direction::Up))
+            {
+                console.cursor_position.1 = row;
+            }
+        }
+        console.scroll_to_cursor(view_order.len());
+        let first = console.scroll_offset;
+        let shown = view_order
+            .len()
+            .saturating_sub(first)
+            .min(console.visible_rows());
+        if let Some(header) = &console.header {
+            // padded so the header reads as a bar across the whole width
+            let width = console.terminal_size.0 as usize;
+            // the today view is not the file as a whole, so the name is left out
+            let header = if self.is_showing_today() {
+                "TODAY"
+            } else {
+                header.as_str()
+            };
+            let (header, background_color) = if self.is_focused() {
+                (
+                    format!("FOCUS  {}", header),
+                    ConsoleBackgroundColors::Yellow,
+                )
+            } else {
+                (header.to_string(), ConsoleBackgroundColors::Cyan)
+            };
+            write!(
+                frame,
+                "{}{}{}",
+                termion::cursor::Goto(1, 1),
+                termion::clear::CurrentLine,
+                get_color_text(
+                    ConsoleForegroundColors::Black,
+                    background_color,
+                    &format!("{:<width$}", header, width = width)
+                )
+            )
+            .unwrap();
+        }
+        for (i, task) in view_order
+            .iter()
+            .map(|&index| &self.tasks[index])
+            .enumerate()
+            .skip(first)
+            .take(shown)
+        {
+            let mut x_position = 1;
+            if self.is_editing && console.cursor_position.1 == (i + 1) as u16 {
+                x_position = 3;
+            }
+            write!(
+                frame,
+                "{}{}",
+                termion::cursor::Goto(x_position, console.screen_row(i as u16 + 1)),
+                termion::clear::CurrentLine
+            )
+            .unwrap();
+            if task.is_header() {
+                let width = console.terminal_size.0 as usize;
+                write!(frame, "{}", header_text(&fit_line(&task.text, 0, width))).unwrap();
+                continue;
+            }
+            let background_color = if console.is_highlighted((i + 1) as u16) {
+                ConsoleBackgroundColors::White
+            } else if task.is_overdue(today) {
+                ConsoleBackgroundColors::Red
+            } else {
+                ConsoleBackgroundColors::None
+            };
+            let mut text = display_line(task, &console.theme);
+            if self.is_editing && console.cursor_position.1 == (i + 1) as u16 {
+                text.push_str(
+                    format!(
+                        " (Current: {}, Next: {})",
+                        task.task_type,
+                        task.task_type.next()
+                    )
+                    .as_str(),
+                );
+            }
+            // without colors the selected task has no highlight, so mark it
+            let marker = if !colors_enabled() && console.is_highlighted((i + 1) as u16) {
+                "> "
+            } else {
+                ""
+            };
+            // long lines are cut at the edge, only the selected one scrolls
+            let width = ((console.terminal_size.0 + 1).saturating_sub(x_position) as usize)
+                .saturating_sub(marker.len());
+            let offset = match console.horizontal_scroll {
+                Some(offset) if console.cursor_position.1 == (i + 1) as u16 => {
+                    let offset = offset.min(text.chars().count().saturating_sub(width));
+                    console.horizontal_scroll = Some(offset);
+                    offset
+                }
+                _ => 0,
+            };
+            // the description as far as it is on screen, past the < and > of a cut line
+            let length = text.chars().count();
+            let last = if length - offset > width {
+                offset + width.saturating_sub(1)
+            } else {
+                length
+            };
+            let first = if offset > 0 { offset + 1 } else { 0 }.min(last);
+            let description = description_range(task, &console.theme);
+            let start = description.start.clamp(first, last);
+            let end = description.end.clamp(start, last);
+            let mut text = format!("{}{}", marker, fit_line(&text, offset, width));
+            let byte = |chars: usize| {
+                text.char_indices()
+                    .nth(chars + marker.len() - offset)
+                    .map_or(text.len(), |(index, _)| index)
+            };
+            let span = byte(start)..byte(end);
+            // pad the selected rows so their highlight spans the whole line
+            if console.is_highlighted((i + 1) as u16) {
+                let width = (console.terminal_size.0 + 1).saturating_sub(x_position) as usize;
+                let length = text.chars().count();
+                if length < width {
+                    text.push_str(&" ".repeat(width - length));
+                }
+            }
+
+            write!(
+                frame,
+                "{}",
+                get_highlighted_text(
+                    task_color(task, &console.theme),
+                    background_color,
+                    text.as_str(),
+                    console.theme.tag,
+                    &self.filter,
+                    span
+                )
+            )
+            .unwrap();
+        }
+
+        // clear rows left over from a longer list
+        write!(
+            frame,
+            "{}{}",
+            termion::cursor::Goto(1, shown as u16 + console.list_top()),
+            termion::clear::AfterCursor
+        )
+        .unwrap();
+
+        let counts = self.counts();
+        let status_bar = [
+            (TaskType::Todo, counts.todo),
+            (TaskType::Doing, counts.doing),
+            (TaskType::Done, counts.done),
+            (TaskType::Rejected, counts.rejected),
+        ]
+        .iter()
+        .map(|(task_type, count)| {
+            get_color_text(
+                console.theme.color(*task_type),
+                ConsoleBackgroundColors::None,
+                format!("{}: {}", task_type, count).as_str(),
+            )
+            .into_owned()
+        })
+        .collect::<Vec<String>>()
+        .join("  ");
+        let status_bar = if self.hidden_types.is_empty() {
+            status_bar
+        } else {
+            let hidden = self
+                .hidden_types
+                .iter()
+                .map(|task_type| task_type.to_string())
+                .collect::<Vec<String>>()
+                .join(", ");
+            format!("{}  hiding {}", status_bar, hidden)
+        };
+        // position of the selected task among the visible ones, filtered or not
+        let (position, tasks_count) = self.task_position(console.cursor_position.1);
+        let selected = self
+            .task_index(console.cursor_position.1)
+            .map(|index| &self.tasks[index]);
+        // length of the selected description, without the status marker
+        let length = selected
+            .map(|task| {
+                let description = get_display_text(task.display_text());
+                format!(
+                    "  {}c {}w",
+                    description.chars().count(),
+                    description.split_whitespace().count()
+                )
+            })
+            .unwrap_or_default();
+        let status_bar = format!(
+            "{}/{}{}  {}  {}",
+            position,
+            tasks_count,
+            length,
+            get_color_text(
+                ConsoleForegroundColors::Green,
+                ConsoleBackgroundColors::None,
+                &counts.progress_bar()
+            ),
+            status_bar
+        );
+        let age = selected
+            .map(|task| {
+                [("added", task.created_at), ("done", task.completed_at)]
+                    .iter()
+                    .filter_map(|(label, date)| {
+                        date.map(|date| format!("{} {}", label, date.age(today)))
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            })
+            .unwrap_or_default();
+        let status_bar = if age.is_empty() {
+            status_bar
+        } else {
+            format!("{}  {}", status_bar, age)
+        };
+        // only lists longer than the screen have pages
+        let status_bar = match console.page_position(position, tasks_count) {
+            (_, 1) => status_bar,
+            (page, pages) => format!("{}  page {}/{}", status_bar, page, pages),
+        };
+        let status_bar = match &console.message {
+            Some(message) => format!(
+                "{}  {}",
+                status_bar,
+                get_color_text(
+                    ConsoleForegroundColors::Red,
+                    ConsoleBackgroundColors::None,
+                    message
+                )
+            ),
+            None => status_bar,
+        };
+        let status_bar = match &console.notice {
+            Some(notice) => format!(
+                "{}  {}",
+                status_bar,
+                get_color_text(
+                    ConsoleForegroundColors::Green,
+                    ConsoleBackgroundColors::None,
+                    notice
+                )
+            ),
+            None => status_bar,
+        };
+        let (_, height) = console.terminal_size;
+        write!(
+            frame,
+            "{}{}{}",
+            termion::cursor::Goto(1, height),
+            termion::clear::CurrentLine,
+            status_bar
+        )
+        .unwrap();
+        if console.debug {
+            write!(
+                frame,
+                "{}{}({}, {})",
+                termion::cursor::Goto(1, height - 1),
+                termion::clear::CurrentLine,
+                console.cursor_position.0,
+                console.cursor_position.1
+            )
+            .unwrap();
+        }
+        frame
+    }
+}
+
+// read a line of text on the given row, returns None when cancelled with Esc
+// on_change is called with the current text after every edit
+pub fn read_input(
+    events: &mut InputEvents,
+    label: &str,
+    initial: &str,
+    row: u16,
+    color: Option<ConsoleForegroundColors>,
+    on_change: &mut dyn FnMut(&str),
+) -> Option<String> {
+    match read_line(events, label, initial, row, color, on_change, false) {
+        LineEdit::Submitted(text) => Some(text),
+        _ => None,
+    }
+}
+
+// how editing a task line was left
+pub enum LineEdit {
+    Submitted(String),
+    // Ctrl-o, split the text at the character offset of the cursor
+    Split(String, usize),
+    Cancelled,
+}
+
+// edit the text of a task on the given row, which can also be split in two
+pub fn edit_line(
+    events: &mut InputEvents,
+    label: &str,
+    initial: &str,
+    row: u16,
+    color: Option<ConsoleForegroundColors>,
+) -> LineEdit {
+    read_line(events, label, initial, row, color, &mut |_| {}, true)
+}
+
+fn read_line(
+    events: &mut InputEvents,
+    label: &str,
+    initial: &str,
+    row: u16,
+    color: Option<ConsoleForegroundColors>,
+    on_change: &mut dyn FnMut(&str),
+    can_split: bool,
+) -> LineEdit {
+    let mut stdout = stdout();
+    let mut input: Vec<char> = initial.chars().collect();
+    let mut position = input.len();
+    let edited = loop {
+        let line: String = label.chars().chain(input.iter().copied()).collect();
+        let line = match color {
+            Some(color) => get_color_text(color, ConsoleBackgroundColors::None, &line),
+            None => line.into(),
+        };
+        write!(
+            stdout,
+            "{}{}{}{}{}",
+            termion::cursor::Goto(1, row),
+            termion::clear::CurrentLine,
+            line,
+            termion::cursor::Goto((label.chars().count() + position) as u16 + 1, row),
+            termion::cursor::Show
+        )
+        .unwrap();
+        stdout.flush().unwrap();
+
+        match events.next() {
+            Some(Ok(Event::Key(Key::Char('\n')))) => {
+                break LineEdit::Submitted(input.iter().collect())
+            }
+            Some(Ok(Event::Key(Key::Ctrl('o')))) if can_split => {
+                break LineEdit::Split(input.iter().collect(), position)
+            }
+            Some(Ok(Event::Key(Key::Char(c)))) if !c.is_control() => {
+                input.insert(position, c);
+                position += 1;
+            }
+            Some(Ok(Event::Key(Key::Backspace))) if position > 0 => {
+                position -= 1;
+                input.remove(position);
+            }
+            Some(Ok(Event::Key(Key::Delete))) if position < input.len() => {
+                input.remove(position);
+            }
+            Some(Ok(Event::Key(Key::Left))) if position > 0 => position -= 1,
+            Some(Ok(Event::Key(Key::Right))) if position < input.len() => position += 1,
+            Some(Ok(Event::Key(Key::Home))) => position = 0,
+            Some(Ok(Event::Key(Key::End))) => position = input.len(),
+            Some(Ok(Event::Key(Key::Esc))) | None => break LineEdit::Cancelled,
+            _ => continue,
+        }
+        on_change(&input.iter().collect::<String>());
+    };
+
+    write!(
+        stdout,
+        "{}{}{}",
+        termion::cursor::Goto(1, row),
+        termion::clear::CurrentLine,
+        termion::cursor::Hide
+    )
+    .unwrap();
+    stdout.flush().unwrap();
+    edited
+}
+
+// show the notes of a task over the whole screen and edit them, returns the new notes when
+// saved with Ctrl-s and None when left with Esc
+pub fn edit_notes(events: &mut InputEvents, title: &str, notes: &str) -> Option<String> {
+    let mut stdout = stdout();
+    let mut input: Vec<char> = notes.chars().collect();
+    let mut position = input.len();
+    let saved = loop {
+        write!(
+            stdout,
+            "{}{}{}{}Ctrl-s to save, Esc to cancel",
+            termion::clear::All,
+            termion::cursor::Goto(1, 1),
+            title,
+            termion::cursor::Goto(1, 2)
+        )
+        .unwrap();
+        let text: String = input.iter().collect();
+        for (i, line) in text.split('\n').enumerate() {
+            write!(stdout, "{}{}", termion::cursor::Goto(1, i as u16 + 4), line).unwrap();
+        }
+        // row and column of the cursor from the text before it
+        let before: String = input[..position].iter().collect();
+        let row = before.matches('\n').count() as u16 + 4;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() as u16 + 1;
+        write!(
+            stdout,
+            "{}{}",
+            termion::cursor::Goto(column, row),
+            termion::cursor::Show
+        )
+        .unwrap();
+        stdout.flush().unwrap();
+
+        match events.next() {
+            Some(Ok(Event::Key(Key::Ctrl('s')))) => break true,
+            Some(Ok(Event::Key(Key::Char(c)))) if c == '\n' || !c.is_control() => {
+                input.insert(position, c);
+                position += 1;
+            }
+            Some(Ok(Event::Key(Key::Backspace))) if position > 0 => {
+                position -= 1;
+                input.remove(position);
+            }
+            Some(Ok(Event::Key(Key::Delete))) if position < input.len() => {
+                input.remove(position);
+            }
+            Some(Ok(Event::Key(Key::Left))) if position > 0 => position -= 1,
+            Some(Ok(Event::Key(Key::Right))) if position < input.len() => position += 1,
+            Some(Ok(Event::Key(Key::Esc))) | None => break false,
+            _ => {}
+        }
+    };
+    write!(stdout, "{}{}", termion::clear::All, termion::cursor::Hide).unwrap();
+    stdout.flush().unwrap();
+
+    if saved {
+        // blank lines and indentation would not survive a save, so they are dropped right away
+        let text: String = input.into_iter().collect();
+        let notes: Vec<&str> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        Some(notes.join("\n"))
+    } else {
+        None
+    }
+}
+
+// draw the key bindings over the whole screen a page at a time, Space, Down or PageDown shows
+// the next page and Up or PageUp the previous one, any other key or the end goes back
+pub fn show_help(events: &mut InputEvents, keymap: &KeyMap, terminal_size: (u16, u16)) {
+    let mut stdout = stdout();
+    let (width, height) = terminal_size;
+    let help = keymap.help();
+    let keys_width = help
+        .iter()
+        .map(|(keys, _)| keys.chars().count())
+        .max()
+        .unwrap_or(0);
+    // the title and an empty row above the keys
+    let per_page = (height as usize).saturating_sub(2).max(1);
+    let pages: Vec<_> = help.chunks(per_page).collect();
+    let mut page = 0;
+    while let Some(rows) = pages.get(page) {
+        let title = if pages.len() > 1 {
+            format!(
+                "Keys, page {} of {} (Space for more, Up to go back, any other key to leave)",
+                page + 1,
+                pages.len()
+            )
+        } else {
+            "Keys (press any key to go back)".to_string()
+        };
+        let mut frame = format!(
+            "{}{}{}",
+            termion::clear::All,
+            termion::cursor::Goto(1, 1),
+            fit_line(&title, 0, width as usize)
+        );
+        for (i, (keys, description)) in rows.iter().enumerate() {
+            let line = format!("  {:<keys_width$} {}", keys, description);
+            let _ = write!(
+                frame,
+                "{}{}",
+                termion::cursor::Goto(1, i as u16 + 3),
+                fit_line(&line, 0, width as usize)
+            );
+        }
+        write!(stdout, "{}", frame).unwrap();
+        stdout.flush().unwrap();
+        match events.next() {
+            Some(Ok(Event::Key(Key::Char(' ') | Key::Down | Key::Char('j') | Key::PageDown))) => {
+                page += 1
+            }
+            Some(Ok(Event::Key(Key::Up | Key::Char('k') | Key::PageUp))) => {
+                page = page.saturating_sub(1)
+            }
+            _ => break,
+        }
+    }
+    write!(stdout, "{}", termion::clear::All).unwrap();
+}
+
+// list the lines over the whole screen and let one be chosen with Up/Down and Enter,
+// returns its index or None when cancelled with Esc or q
+pub fn pick_line(
+    events: &mut InputEvents,
+    title: &str,
+    lines: &[String],
+    terminal_size: (u16, u16),
+) -> Option<usize> {
+    let mut stdout = stdout();
+    let (width, height) = terminal_size;
+    let visible = (height as usize).saturating_sub(1).max(1);
+    let mut selected = 0;
+    let mut offset = 0;
+    let picked = loop {
+        if selected < offset {
+            offset = selected;
+        } else if selected >= offset + visible {
+            offset = selected + 1 - visible;
+        }
+        let mut frame = format!(
+            "{}{}{}",
+            termion::clear::All,
+            termion::cursor::Goto(1, 1),
+            fit_line(title, 0, width as usize)
+        );
+        for (row, line) in lines.iter().enumerate().skip(offset).take(visible) {
+            let line = fit_line(line, 0, width as usize);
+            let line = if row == selected {
+                format!("{}{}{}", style::Invert, line, style::Reset)
+            } else {
+                line
+            };
+            let _ = write!(
+                frame,
+                "{}{}",
+                termion::cursor::Goto(1, (row - offset) as u16 + 2),
+                line
+            );
+        }
+        write!(stdout, "{}{}", frame, termion::cursor::Hide).unwrap();
+        stdout.flush().unwrap();
+
+        match events.next() {
+            Some(Ok(Event::Key(Key::Char('\n')))) => break Some(selected),
+            Some(Ok(Event::Key(Key::Up | Key::Char('k')))) => selected = selected.saturating_sub(1),
+            Some(Ok(Event::Key(Key::Down | Key::Char('j')))) => {
+                selected = (selected + 1).min(lines.len().saturating_sub(1))
+            }
+            Some(Ok(Event::Key(Key::Esc | Key::Char('q')))) | None => break None,
+            _ => {}
+        }
+    };
+    write!(stdout, "{}", termion::clear::All).unwrap();
+    stdout.flush().unwrap();
+    picked.filter(|_| !lines.is_empty())
+}
+
+// ask a question on the given row and wait for a single key, None when it is not a character
+pub fn read_choice(events: &mut InputEvents, question: &str, row: u16) -> Option<char> {
+    let mut stdout = stdout();
+    write!(
+        stdout,
+        "{}{}{}",
+        termion::cursor::Goto(1, row),
+        termion::clear::CurrentLine,
+        question
+    )
+    .unwrap();
+    stdout.flush().unwrap();
+
+    let choice = match events.next() {
+        Some(Ok(Event::Key(Key::Char(c)))) => Some(c.to_ascii_lowercase()),
+        _ => None,
+    };
+
+    write!(
+        stdout,
+        "{}{}",
+        termion::cursor::Goto(1, row),
+        termion::clear::CurrentLine
+    )
+    .unwrap();
+    stdout.flush().unwrap();
+    choice
+}
+
+// printed for each file on quitting, e.g. "3 todo, 1 doing remaining in list.txt"
+pub fn remaining_summary(counts: &TaskCounts, file_path: &str, theme: &Theme) -> String {
+    let remaining: Vec<String> = [
+        (TaskType::Todo, counts.todo),
+        (TaskType::Doing, counts.doing),
+    ]
+    .iter()
+    .filter(|&&(_, count)| count > 0)
+    .map(|&(task_type, count)| {
+        get_color_text(
+            theme.color(task_type),
+            ConsoleBackgroundColors::None,
+            &format!("{} {}", count, task_type.as_keyword()),
+        )
+        .into_owned()
+    })
+    .collect();
+    if remaining.is_empty() {
+        format!("Nothing remaining in {}", file_path)
+    } else {
+        format!("{} remaining in {}", remaining.join(", "), file_path)
+    }
+}
+
+// header bar with the open file, and its position among the tabs when there are several
+pub fn tab_header(file_paths: &[String], active: usize, read_only: bool) -> String {
+    let mut header = if file_paths.len() > 1 {
+        format!(
+            "[{}/{}] {}",
+            active + 1,
+            file_paths.len(),
+            file_paths[active]
+        )
+    } else {
+        file_paths[active].clone()
+    };
+    if read_only {
+        header.push_str(" [RO]");
+    }
+    header
+}
+
+// the last cursor row of a list is kept next to it, e.g. .list.txt.cursor for list.txt
+pub fn cursor_path(file_path: &str) -> PathBuf {
+    let path = Path::new(file_path);
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.cursor", file_name))
+}
+
+// the cursor row saved for the list, 1 when there is none or it cannot be read
+pub fn load_cursor(file_path: &str) -> u16 {
+    std::fs::read_to_string(cursor_path(file_path))
+        .ok()
+        .and_then(|content| content.trim().parse::<u16>().ok())
+        .filter(|&row| row > 0)
+        .unwrap_or(1)
+}
+
+pub fn save_cursor(file_path: &str, row: u16) -> io::Result<()> {
+    std::fs::write(cursor_path(file_path), format!("{}\n", row))
+}
+
+// keys that change a list, ignored with --read-only
+pub fn is_mutating(event: &Event, keymap: &KeyMap) -> bool {
+    match event {
+        Event::Key(key) => keymap
+            .action(key)
+            .is_some_and(|action| action.is_mutating()),
+        _ => false,
+    }
+}
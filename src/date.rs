@@ -0,0 +1,132 @@
+use core::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    // parse a YYYY-MM-DD date, None when malformed or not a real day
+    pub fn parse(text: &str) -> Option<Date> {
+        let mut parts = text.trim().split('-');
+        let year: i32 = parts.next()?.parse().ok()?;
+        let month: u32 = parts.next()?.parse().ok()?;
+        let day: u32 = parts.next()?.parse().ok()?;
+        if parts.next().is_some() || !(1..=12).contains(&month) {
+            return None;
+        }
+        if day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Date { year, month, day })
+    }
+
+    // current date in the local time zone, so the day turns over at local midnight
+    pub fn today() -> Date {
+        let seconds = now_seconds() as i64;
+        Date::from_days((seconds + utc_offset(seconds)).div_euclid(DAY as i64))
+    }
+
+    // days since 1970-01-01
+    pub fn days_since_epoch(&self) -> i64 {
+        let year = self.year as i64 - if self.month <= 2 { 1 } else { 0 };
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let month_index = (self.month as i64 + 9) % 12;
+        let day_of_year = (153 * month_index + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+
+    // how long ago the date was, e.g. "today", "3d ago" or "2w ago"
+    pub fn age(&self, today: Date) -> String {
+        match today.days_since_epoch() - self.days_since_epoch() {
+            ..=0 => "today".to_string(),
+            days => format!("{} ago", humanize_duration(days as u64 * DAY)),
+        }
+    }
+
+    // civil date from days since 1970-01-01
+    pub fn from_days(days: i64) -> Date {
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days.rem_euclid(146097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        } as u32;
+        let year = (year_of_era + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+        Date { year, month, day }
+    }
+}
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+// a number of seconds in its largest whole unit, e.g. "5m", "3h", "2d" or "1w",
+// anything under a minute is "just now"
+pub fn humanize_duration(seconds: u64) -> String {
+    match seconds {
+        0..MINUTE => "just now".to_string(),
+        MINUTE..HOUR => format!("{}m", seconds / MINUTE),
+        HOUR..DAY => format!("{}h", seconds / HOUR),
+        DAY..WEEK => format!("{}d", seconds / DAY),
+        _ => format!("{}w", seconds / WEEK),
+    }
+}
+
+fn now_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+// seconds the local time zone is ahead of UTC at the time, 0 when it cannot be told
+fn utc_offset(seconds: i64) -> i64 {
+    let time = seconds as libc::time_t;
+    // SAFETY: localtime_r only writes to the tm it is given, a zeroed tm is a valid value
+    let mut local: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut local) }.is_null() {
+        return 0;
+    }
+    local.tm_gmtoff as i64
+}
+
+// current date and time in UTC, e.g. 2024-05-01T13:45:00Z
+pub fn timestamp() -> String {
+    let seconds = now_seconds();
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        Date::from_days((seconds / 86400) as i64),
+        seconds % 86400 / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
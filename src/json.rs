@@ -0,0 +1,248 @@
+use std::iter::Peekable;
+use std::path::Path;
+use std::str::Chars;
+
+use crate::task::{get_description, get_display_text, Task, TaskType};
+
+pub fn is_json_path(file_path: &str) -> bool {
+    Path::new(file_path)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+pub fn tasks_to_json(tasks: &[Task]) -> String {
+    let mut json = String::from("[\n");
+    for (i, task) in tasks.iter().enumerate() {
+        json.push_str(
+            format!(
+                "  {{\"task_type\": {}, \"text\": {}, \"notes\": {}}}",
+                json_string(&task.task_type.to_string()),
+                json_string(&task.line()),
+                json_string(&task.notes)
+            )
+            .as_str(),
+        );
+        if i + 1 < tasks.len() {
+            json.push(',');
+        }
+        json.push('\n');
+    }
+    json.push_str("]\n");
+    json
+}
+
+// the tasks for other programs, one {"status", "text"} object per task with the status keyword
+// and the description without markers and stamps, comments are left out
+pub fn tasks_to_status_json(tasks: &[Task]) -> String {
+    let items: Vec<String> = tasks
+        .iter()
+        .filter(|task| !task.is_comment())
+        .map(|task| {
+            format!(
+                "  {{\"status\": {}, \"text\": {}}}",
+                json_string(task.task_type.as_keyword()),
+                json_string(&get_display_text(get_description(&task.text)))
+            )
+        })
+        .collect();
+    if items.is_empty() {
+        return "[]\n".to_string();
+    }
+    format!("[\n{}\n]\n", items.join(",\n"))
+}
+
+pub fn tasks_from_json(content: &str) -> Option<Vec<Task>> {
+    if content.trim().is_empty() {
+        return Some(Vec::new());
+    }
+    let items = match JsonValue::parse(content)? {
+        JsonValue::Array(items) => items,
+        _ => return None,
+    };
+    let mut tasks = Vec::new();
+    for item in items {
+        let text = item.get("text")?.as_str()?.to_string();
+        // saved as the Debug name, e.g. "Doing", which reads as the keyword
+        let task_type = TaskType::from_keyword(item.get("task_type")?.as_str()?)
+            .unwrap_or(TaskType::NotDefined);
+        let mut task = Task::new(&text, task_type);
+        // lists saved before notes existed have none
+        if let Some(notes) = item.get("notes") {
+            task.notes = notes.as_str()?.to_string();
+        }
+        tasks.push(task);
+    }
+    Some(tasks)
+}
+
+fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(format!("\\u{:04x}", c as u32).as_str()),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+enum JsonValue {
+    // null, true, false or a number, the tasks have no use for their value
+    Scalar,
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    fn parse(text: &str) -> Option<JsonValue> {
+        let mut chars = text.chars().peekable();
+        let value = JsonValue::parse_value(&mut chars)?;
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            None => Some(value),
+            Some(_) => None,
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(text) => Some(text.as_str()),
+            _ => None,
+        }
+    }
+
+    fn parse_value(chars: &mut Peekable<Chars>) -> Option<JsonValue> {
+        skip_whitespace(chars);
+        match chars.peek()? {
+            '{' => {
+                chars.next();
+                let mut fields = Vec::new();
+                skip_whitespace(chars);
+                if chars.peek() == Some(&'}') {
+                    chars.next();
+                    return Some(JsonValue::Object(fields));
+                }
+                loop {
+                    skip_whitespace(chars);
+                    let key = parse_json_string(chars)?;
+                    skip_whitespace(chars);
+                    if chars.next()? != ':' {
+                        return None;
+                    }
+                    fields.push((key, JsonValue::parse_value(chars)?));
+                    skip_whitespace(chars);
+                    match chars.next()? {
+                        ',' => continue,
+                        '}' => return Some(JsonValue::Object(fields)),
+                        _ => return None,
+                    }
+                }
+            }
+            '[' => {
+                chars.next();
+                let mut items = Vec::new();
+                skip_whitespace(chars);
+                if chars.peek() == Some(&']') {
+                    chars.next();
+                    return Some(JsonValue::Array(items));
+                }
+                loop {
+                    items.push(JsonValue::parse_value(chars)?);
+                    skip_whitespace(chars);
+                    match chars.next()? {
+                        ',' => continue,
+                        ']' => return Some(JsonValue::Array(items)),
+                        _ => return None,
+                    }
+                }
+            }
+            '"' => Some(JsonValue::String(parse_json_string(chars)?)),
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '-' || c == '+' || c == '.' {
+                        word.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                match word.as_str() {
+                    "null" | "true" | "false" => Some(JsonValue::Scalar),
+                    _ => word.parse::<f64>().ok().map(|_| JsonValue::Scalar),
+                }
+            }
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+fn parse_json_string(chars: &mut Peekable<Chars>) -> Option<String> {
+    if chars.next()? != '"' {
+        return None;
+    }
+    let mut text = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(text),
+            '\\' => match chars.next()? {
+                'n' => text.push('\n'),
+                'r' => text.push('\r'),
+                't' => text.push('\t'),
+                'b' => text.push('\u{8}'),
+                'f' => text.push('\u{c}'),
+                'u' => {
+                    let code = parse_hex_code(chars)?;
+                    // characters outside the basic plane, e.g. emoji, come as a pair of
+                    // surrogates; half of a pair on its own is not a character
+                    let c = if (0xd800..0xdc00).contains(&code) {
+                        let mut rest = chars.clone();
+                        let low = (rest.next() == Some('\\') && rest.next() == Some('u'))
+                            .then(|| parse_hex_code(&mut rest))
+                            .flatten()
+                            .filter(|low| (0xdc00..0xe000).contains(low));
+                        match low {
+                            Some(low) => {
+                                *chars = rest;
+                                char::from_u32(0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00))
+                            }
+                            None => None,
+                        }
+                    } else {
+                        char::from_u32(code)
+                    };
+                    text.push(c.unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                c => text.push(c),
+            },
+            c => text.push(c),
+        }
+    }
+}
+
+fn parse_hex_code(chars: &mut Peekable<Chars>) -> Option<u32> {
+    let code: String = (0..4).filter_map(|_| chars.next()).collect();
+    u32::from_str_radix(&code, 16).ok()
+}
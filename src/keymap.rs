@@ -0,0 +1,538 @@
+use std::collections::HashMap;
+
+use termion::event::Key;
+
+// what a key does in the list, the main loop matches on these instead of on the keys
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum Action {
+    Quit,
+    NextFile,
+    PreviousFile,
+    Add,
+    InsertBelow,
+    InsertAbove,
+    Notes,
+    Edit,
+    DueDate,
+    Color,
+    ClearCompleted,
+    MergeDuplicates,
+    Archive,
+    Unarchive,
+    Duplicate,
+    MarkAllDone,
+    Fix,
+    MergeNext,
+    Delete,
+    Help,
+    Search,
+    TagFilter,
+    Today,
+    Focus,
+    ToggleTodo,
+    ToggleDoing,
+    ToggleDone,
+    ToggleRejected,
+    Select,
+    // leave the current mode, quit from the plain list
+    Back,
+    MoveUp,
+    MoveDown,
+    MoveToTop,
+    MoveToBottom,
+    Sort,
+    Undo,
+    Redo,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Jump,
+    Scroll,
+    SetTodo,
+    SetDoing,
+    SetDone,
+    SetRejected,
+    // Right by default, also scrolls right while scrolling
+    CycleStatus,
+    // Left by default, also scrolls left while scrolling
+    StopCycling,
+    CycleStatusBack,
+    RaisePriority,
+    LowerPriority,
+}
+
+// names of the actions in the [keys] section of the config file
+pub const ACTION_NAMES: &[(&str, Action)] = &[
+    ("quit", Action::Quit),
+    ("next-file", Action::NextFile),
+    ("previous-file", Action::PreviousFile),
+    ("add", Action::Add),
+    ("insert-below", Action::InsertBelow),
+    ("insert-above", Action::InsertAbove),
+    ("notes", Action::Notes),
+    ("edit", Action::Edit),
+    ("due-date", Action::DueDate),
+    ("color", Action::Color),
+    ("clear-completed", Action::ClearCompleted),
+    ("merge-duplicates", Action::MergeDuplicates),
+    ("archive", Action::Archive),
+    ("unarchive", Action::Unarchive),
+    ("duplicate", Action::Duplicate),
+    ("mark-all-done", Action::MarkAllDone),
+    ("fix", Action::Fix),
+    ("merge-next", Action::MergeNext),
+    ("delete", Action::Delete),
+    ("help", Action::Help),
+    ("search", Action::Search),
+    ("tag-filter", Action::TagFilter),
+    ("today", Action::Today),
+    ("focus", Action::Focus),
+    ("toggle-todo", Action::ToggleTodo),
+    ("toggle-doing", Action::ToggleDoing),
+    ("toggle-done", Action::ToggleDone),
+    ("toggle-rejected", Action::ToggleRejected),
+    ("select", Action::Select),
+    ("back", Action::Back),
+    ("move-up", Action::MoveUp),
+    ("move-down", Action::MoveDown),
+    ("move-to-top", Action::MoveToTop),
+    ("move-to-bottom", Action::MoveToBottom),
+    ("sort", Action::Sort),
+    ("undo", Action::Undo),
+    ("redo", Action::Redo),
+    ("up", Action::Up),
+    ("down", Action::Down),
+    ("page-up", Action::PageUp),
+    ("page-down", Action::PageDown),
+    ("jump", Action::Jump),
+    ("scroll", Action::Scroll),
+    ("set-todo", Action::SetTodo),
+    ("set-doing", Action::SetDoing),
+    ("set-done", Action::SetDone),
+    ("set-rejected", Action::SetRejected),
+    ("cycle-status", Action::CycleStatus),
+    ("stop-cycling", Action::StopCycling),
+    ("cycle-status-back", Action::CycleStatusBack),
+    ("raise-priority", Action::RaisePriority),
+    ("lower-priority", Action::LowerPriority),
+];
+
+// used unless the config file binds the action elsewhere
+const DEFAULT_KEYS: &[(Key, Action)] = &[
+    (Key::Char('q'), Action::Quit),
+    (Key::Char('\t'), Action::NextFile),
+    (Key::BackTab, Action::PreviousFile),
+    (Key::Char('n'), Action::Add),
+    (Key::Char('a'), Action::Add),
+    (Key::Char('o'), Action::InsertBelow),
+    (Key::Char('O'), Action::InsertAbove),
+    (Key::Char('\n'), Action::Notes),
+    (Key::Char('e'), Action::Edit),
+    (Key::Char('D'), Action::DueDate),
+    (Key::Char('c'), Action::Color),
+    (Key::Char('C'), Action::ClearCompleted),
+    (Key::Char('M'), Action::MergeDuplicates),
+    (Key::Char('X'), Action::Archive),
+    (Key::Char('R'), Action::Unarchive),
+    (Key::Char('y'), Action::Duplicate),
+    (Key::Char('A'), Action::MarkAllDone),
+    (Key::Char('F'), Action::Fix),
+    (Key::Char('m'), Action::MergeNext),
+    (Key::Char('d'), Action::Delete),
+    (Key::Char('?'), Action::Help),
+    (Key::Char('/'), Action::Search),
+    (Key::Char('#'), Action::TagFilter),
+    (Key::Char('T'), Action::Today),
+    (Key::Char('f'), Action::Focus),
+    (Key::Char('1'), Action::ToggleTodo),
+    (Key::Char('2'), Action::ToggleDoing),
+    (Key::Char('3'), Action::ToggleDone),
+    (Key::Char('4'), Action::ToggleRejected),
+    (Key::Char('V'), Action::Select),
+    (Key::Esc, Action::Back),
+    (Key::Char('K'), Action::MoveUp),
+    (Key::Char('J'), Action::MoveDown),
+    (Key::Home, Action::MoveToTop),
+    (Key::End, Action::MoveToBottom),
+    (Key::Char('s'), Action::Sort),
+    (Key::Char('u'), Action::Undo),
+    (Key::Ctrl('r'), Action::Redo),
+    (Key::Up, Action::Up),
+    (Key::Char('k'), Action::Up),
+    (Key::Down, Action::Down),
+    (Key::Char('j'), Action::Down),
+    (Key::PageUp, Action::PageUp),
+    (Key::PageDown, Action::PageDown),
+    (Key::Char(':'), Action::Jump),
+    (Key::Char('z'), Action::Scroll),
+    (Key::Char('t'), Action::SetTodo),
+    (Key::Char('g'), Action::SetDoing),
+    (Key::Char('x'), Action::SetDone),
+    (Key::Char('r'), Action::SetRejected),
+    (Key::Right, Action::CycleStatus),
+    (Key::Char('l'), Action::CycleStatus),
+    (Key::Left, Action::StopCycling),
+    (Key::Char('h'), Action::StopCycling),
+    (Key::ShiftRight, Action::CycleStatusBack),
+    (Key::ShiftUp, Action::RaisePriority),
+    (Key::ShiftDown, Action::LowerPriority),
+];
+
+// what the actions do, in the order of the help; the keys are looked up in the KeyMap so
+// the help shows the ones of the config file
+const HELP: &[(&[Action], &str)] = &[
+    (&[Action::Up, Action::Down], "move the cursor"),
+    (
+        &[Action::CycleStatus],
+        "cycle the status of the selected task",
+    ),
+    (
+        &[Action::CycleStatusBack],
+        "cycle the status of the selected task backwards",
+    ),
+    (&[Action::StopCycling], "stop editing and save the list"),
+    (
+        &[Action::RaisePriority, Action::LowerPriority],
+        "raise or lower the priority of the selected task",
+    ),
+    (
+        &[
+            Action::SetTodo,
+            Action::SetDoing,
+            Action::SetDone,
+            Action::SetRejected,
+        ],
+        "set the status of the selected tasks to todo, doing, done or rejected",
+    ),
+    (&[Action::Add], "add a new task"),
+    (
+        &[Action::InsertBelow, Action::InsertAbove],
+        "insert a new task below or above the selected one",
+    ),
+    (
+        &[Action::Notes],
+        "show and edit the notes of the selected task",
+    ),
+    (
+        &[Action::Edit],
+        "edit the text of the selected task, Ctrl-o splits it at the cursor",
+    ),
+    (&[Action::Delete], "delete the selected task"),
+    (
+        &[Action::MergeNext],
+        "join the selected task with the one below it, keeping its status",
+    ),
+    (
+        &[Action::Duplicate],
+        "duplicate the selected task below it as a new todo",
+    ),
+    (
+        &[Action::DueDate],
+        "set or clear the due date of the selected task",
+    ),
+    (
+        &[Action::Color],
+        "cycle the color of the selected task, back to the color of its status",
+    ),
+    (
+        &[Action::ClearCompleted],
+        "remove all done and rejected tasks",
+    ),
+    (
+        &[Action::MergeDuplicates],
+        "merge tasks with the same text, keeping the most advanced status",
+    ),
+    (
+        &[Action::Archive],
+        "move all done and rejected tasks to FILE.archive.txt",
+    ),
+    (
+        &[Action::Unarchive],
+        "pick an archived task and restore it to the end of the list",
+    ),
+    (
+        &[Action::MarkAllDone],
+        "mark every shown task done, only the matching ones while searching",
+    ),
+    (
+        &[Action::Fix],
+        "give every task without a status the todo status",
+    ),
+    (
+        &[Action::Search],
+        "search, only tasks containing the text are shown",
+    ),
+    (
+        &[Action::TagFilter],
+        "show only the tasks with a #tag, empty to show all",
+    ),
+    (
+        &[
+            Action::ToggleTodo,
+            Action::ToggleDoing,
+            Action::ToggleDone,
+            Action::ToggleRejected,
+        ],
+        "hide or show the todo, doing, done or rejected tasks",
+    ),
+    (
+        &[Action::Today],
+        "show only the doing tasks and the ones due today, press again to show all",
+    ),
+    (
+        &[Action::Focus],
+        "focus on the doing tasks, press again to show the rest",
+    ),
+    (
+        &[Action::Select],
+        "select a range of tasks, Right/Shift-Right then change all their statuses",
+    ),
+    (
+        &[Action::Back],
+        "clear the selection, then the search, the tag filter and the today view, then quit",
+    ),
+    (
+        &[Action::Scroll],
+        "scroll a long selected task with Left/Right, press again to stop",
+    ),
+    (
+        &[Action::PageUp, Action::PageDown],
+        "move a screen up or down",
+    ),
+    (
+        &[Action::Jump],
+        "type a task number and jump to it, headers are not counted",
+    ),
+    (
+        &[Action::MoveUp, Action::MoveDown],
+        "move the selected task up or down",
+    ),
+    (
+        &[Action::MoveToTop, Action::MoveToBottom],
+        "move the selected task to the top or the bottom of the list",
+    ),
+    (
+        &[Action::Sort],
+        "cycle sorting by status, open before finished, text or insertion order",
+    ),
+    (&[Action::Undo], "undo the last change"),
+    (&[Action::Redo], "redo the last undone change"),
+    (
+        &[Action::NextFile, Action::PreviousFile],
+        "save and switch to the next or previous file",
+    ),
+    (&[Action::Help], "show these keys"),
+    (
+        &[Action::Quit],
+        "save and quit, asks first when there are unsaved changes",
+    ),
+];
+
+// keys that cannot be remapped, listed after the others in the help
+const FIXED_KEYS: &[(&str, &str)] = &[(
+    "click",
+    "select a task, click it again or right click to cycle its status",
+)];
+
+impl Action {
+    pub fn from_name(name: &str) -> Option<Action> {
+        ACTION_NAMES
+            .iter()
+            .find(|(action_name, _)| *action_name == name.trim().to_lowercase())
+            .map(|&(_, action)| action)
+    }
+
+    pub fn name(&self) -> &'static str {
+        ACTION_NAMES
+            .iter()
+            .find(|(_, action)| action == self)
+            .map_or("", |&(name, _)| name)
+    }
+
+    // refused in read-only mode
+    pub fn is_mutating(&self) -> bool {
+        matches!(
+            self,
+            Action::Add
+                | Action::InsertBelow
+                | Action::InsertAbove
+                | Action::Edit
+                | Action::DueDate
+                | Action::Color
+                | Action::ClearCompleted
+                | Action::MergeDuplicates
+                | Action::Archive
+                | Action::Unarchive
+                | Action::Duplicate
+                | Action::MarkAllDone
+                | Action::Fix
+                | Action::MergeNext
+                | Action::Delete
+                | Action::MoveUp
+                | Action::MoveDown
+                | Action::MoveToTop
+                | Action::MoveToBottom
+                | Action::Undo
+                | Action::Redo
+                | Action::SetTodo
+                | Action::SetDoing
+                | Action::SetDone
+                | Action::SetRejected
+                | Action::CycleStatus
+                | Action::CycleStatusBack
+                | Action::RaisePriority
+                | Action::LowerPriority
+        )
+    }
+}
+
+pub struct KeyMap {
+    bindings: HashMap<Key, Action>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self {
+            bindings: DEFAULT_KEYS.iter().cloned().collect(),
+        }
+    }
+}
+
+impl KeyMap {
+    pub fn action(&self, key: &Key) -> Option<Action> {
+        self.bindings.get(key).copied()
+    }
+
+    // the keys of the action, the default ones in their usual order before any others
+    pub fn keys(&self, action: Action) -> Vec<Key> {
+        let mut keys: Vec<Key> = DEFAULT_KEYS
+            .iter()
+            .filter(|(key, _)| self.action(key) == Some(action))
+            .map(|&(key, _)| key)
+            .collect();
+        let mut others: Vec<Key> = self
+            .bindings
+            .iter()
+            .filter(|&(key, &bound)| bound == action && !keys.contains(key))
+            .map(|(&key, _)| key)
+            .collect();
+        others.sort_by_key(|&key| key_name(key));
+        keys.extend(others);
+        keys
+    }
+
+    // the rows of the help, the keys as e.g. "up, k / down, j" and what they do
+    pub fn help(&self) -> Vec<(String, &'static str)> {
+        let mut rows: Vec<(String, &'static str)> = HELP
+            .iter()
+            .map(|&(actions, description)| {
+                let keys = actions
+                    .iter()
+                    .map(|&action| {
+                        let keys: Vec<String> =
+                            self.keys(action).into_iter().map(key_name).collect();
+                        if keys.is_empty() {
+                            "unbound".to_string()
+                        } else {
+                            keys.join(", ")
+                        }
+                    })
+                    .collect::<Vec<String>>()
+                    .join(" / ");
+                (keys, description)
+            })
+            .collect();
+        rows.extend(
+            FIXED_KEYS
+                .iter()
+                .map(|&(keys, description)| (keys.to_string(), description)),
+        );
+        rows
+    }
+
+    // the action keeps only the given keys, returns the actions that lost one of them
+    pub fn rebind(&mut self, action: Action, keys: &[Key]) -> Vec<(Key, Action)> {
+        self.bindings.retain(|_, bound| *bound != action);
+        keys.iter()
+            .filter_map(|&key| {
+                self.bindings
+                    .insert(key, action)
+                    .filter(|&previous| previous != action)
+                    .map(|previous| (key, previous))
+            })
+            .collect()
+    }
+}
+
+// a key as written in the config file: a single character, a name like space, enter,
+// tab, esc, up or pagedown, f1 to f12, or ctrl- or alt- and a character
+pub fn parse_key(name: &str) -> Option<Key> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(Key::Char(c));
+    }
+    let lowercase = name.to_lowercase();
+    let single = |rest: &str| {
+        let mut chars = rest.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    };
+    if let Some(rest) = lowercase.strip_prefix("ctrl-") {
+        return single(rest).map(Key::Ctrl);
+    }
+    if let Some(rest) = name.get(4..).filter(|_| lowercase.starts_with("alt-")) {
+        return single(rest).map(Key::Alt);
+    }
+    if let Some(number) = lowercase
+        .strip_prefix('f')
+        .and_then(|n| n.parse::<u8>().ok())
+    {
+        return (1..=12).contains(&number).then_some(Key::F(number));
+    }
+    match lowercase.as_str() {
+        "space" => Some(Key::Char(' ')),
+        "enter" => Some(Key::Char('\n')),
+        "tab" => Some(Key::Char('\t')),
+        "shift-tab" | "backtab" => Some(Key::BackTab),
+        "esc" | "escape" => Some(Key::Esc),
+        "backspace" => Some(Key::Backspace),
+        "delete" => Some(Key::Delete),
+        "insert" => Some(Key::Insert),
+        "up" => Some(Key::Up),
+        "down" => Some(Key::Down),
+        "left" => Some(Key::Left),
+        "right" => Some(Key::Right),
+        "shift-up" => Some(Key::ShiftUp),
+        "shift-down" => Some(Key::ShiftDown),
+        "shift-left" => Some(Key::ShiftLeft),
+        "shift-right" => Some(Key::ShiftRight),
+        "home" => Some(Key::Home),
+        "end" => Some(Key::End),
+        "pageup" => Some(Key::PageUp),
+        "pagedown" => Some(Key::PageDown),
+        _ => None,
+    }
+}
+
+// inverse of parse_key, for messages about the bindings
+pub fn key_name(key: Key) -> String {
+    match key {
+        Key::Char(' ') => "space".to_string(),
+        Key::Char('\n') => "enter".to_string(),
+        Key::Char('\t') => "tab".to_string(),
+        Key::Char(c) => c.to_string(),
+        Key::Ctrl(c) => format!("ctrl-{}", c),
+        Key::Alt(c) => format!("alt-{}", c),
+        Key::F(number) => format!("f{}", number),
+        Key::BackTab => "shift-tab".to_string(),
+        Key::Esc => "esc".to_string(),
+        Key::ShiftUp => "shift-up".to_string(),
+        Key::ShiftDown => "shift-down".to_string(),
+        Key::ShiftLeft => "shift-left".to_string(),
+        Key::ShiftRight => "shift-right".to_string(),
+        key => format!("{:?}", key).to_lowercase(),
+    }
+}
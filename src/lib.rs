@@ -0,0 +1,15 @@
+// the todo list model, its file formats and the terminal interface,
+// the todo binary is a thin command line wrapper around them
+pub mod color;
+pub mod config;
+pub mod console;
+pub mod date;
+pub mod json;
+pub mod keymap;
+pub mod markdown;
+pub mod task;
+pub mod todo_list;
+pub mod tsv;
+
+pub use task::{get_type_from_string, type_to_string, Task, TaskType};
+pub use todo_list::TodoList;
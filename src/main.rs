@@ -1,362 +1,598 @@
-use core::fmt;
-use std::borrow::BorrowMut;
 use std::env;
-use std::io::{stdin, stdout};
-use std::ops::Add;
-use std::{
-    fs::File,
-    io::{BufRead, BufReader, BufWriter, Write},
-};
-use termion::event::Key;
-use termion::input::TermRead;
-use termion::raw::IntoRawMode;
+use std::io::{self, stdin, stdout, Write};
+use std::path::Path;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::{Duration, Instant};
 
-enum ConsoleForegroundColors {
-    Black = 30,
-    Red = 31,
-    Green = 32,
-    Yellow = 33,
-    Blue = 34,
-    Magenta = 35,
-    Cyan = 36,
-    White = 37,
-}
+use termion::event::Event;
+use termion::event::{MouseButton, MouseEvent};
+use termion::input::MouseTerminal;
+use termion::raw::IntoRawMode;
 
-#[derive(PartialEq, Eq)]
-enum ConsoleBackgroundColors {
-    None = 0,
-    Black = 40,
-    Red = 41,
-    Green = 42,
-    Yellow = 43,
-    Blue = 44,
-    Magenta = 45,
-    Cyan = 46,
-    White = 47,
-}
+use todo::color::{disable_colors, next_override_color};
+use todo::config::{Config, AUTOSAVE_SECONDS};
+use todo::console::{
+    edit_line, edit_notes, is_mutating, load_cursor, pick_line, read_choice, read_input,
+    remaining_summary, save_cursor, show_help, tab_header, Console, InputEvents, LineEdit,
+    RESIZE_POLL,
+};
+use todo::date::Date;
+use todo::json::tasks_to_status_json;
+use todo::keymap::{Action, KeyMap, ACTION_NAMES};
+use todo::markdown::{from_markdown, to_markdown};
+use todo::task::{
+    compose_task_text, get_description, parse_status_cycle, set_status_cycle, Priority, TaskType,
+    INDENT,
+};
+use todo::todo_list::{archive_path, archived_tasks, log_path, Direction, FileFormat, SortMode};
+use todo::TodoList;
 
-#[derive(PartialEq, Eq, PartialOrd, Debug)]
-enum TaskType {
-    Todo,
-    Doing,
-    Done,
-    Rejected,
-    NotDefined,
-}
-impl fmt::Display for TaskType {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-impl Copy for TaskType {}
+// confirmation on the status bar after the list was written
+const SAVED: &str = "Saved ✓";
 
-impl Clone for TaskType {
-    fn clone(&self) -> TaskType {
-        *self
+// save a list from the interactive mode, a failure is shown on the status bar, returns
+// whether the list was saved
+fn save_or_report(todo_list: &mut TodoList, file_path: &str, console: &mut Console) -> bool {
+    if console.read_only {
+        return false;
     }
-}
-impl Ord for TaskType {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        match self {
-            TaskType::Todo => match other {
-                TaskType::Todo => std::cmp::Ordering::Equal,
-                _ => std::cmp::Ordering::Less,
-            },
-            TaskType::Doing => match other {
-                TaskType::Todo => std::cmp::Ordering::Greater,
-                TaskType::Doing => std::cmp::Ordering::Equal,
-                _ => std::cmp::Ordering::Less,
-            },
-            TaskType::Done => match other {
-                TaskType::Todo => std::cmp::Ordering::Greater,
-                TaskType::Doing => std::cmp::Ordering::Greater,
-                TaskType::Done => std::cmp::Ordering::Equal,
-                _ => std::cmp::Ordering::Less,
-            },
-            TaskType::Rejected => match other {
-                TaskType::Todo => std::cmp::Ordering::Greater,
-                TaskType::Doing => std::cmp::Ordering::Greater,
-                TaskType::Done => std::cmp::Ordering::Greater,
-                TaskType::Rejected => std::cmp::Ordering::Equal,
-                _ => std::cmp::Ordering::Less,
-            },
-            TaskType::NotDefined => match other {
-                TaskType::NotDefined => std::cmp::Ordering::Equal,
-                _ => std::cmp::Ordering::Greater,
-            },
+    match todo_list.save(file_path) {
+        Ok(()) => true,
+        Err(error) => {
+            console.message = Some(format!("Save failed: {}", error));
+            false
         }
     }
 }
 
-// implement for task type option to get next type in order
-impl TaskType {
-    fn next(&self) -> TaskType {
-        match self {
-            TaskType::Todo => TaskType::Doing,
-            TaskType::Doing => TaskType::Done,
-            TaskType::Done => TaskType::Rejected,
-            TaskType::Rejected => TaskType::Todo,
-            TaskType::NotDefined => TaskType::NotDefined,
+// save every changed list, returns how many were saved
+fn autosave(todo_lists: &mut [TodoList], file_paths: &[String], console: &mut Console) -> usize {
+    let mut saved = 0;
+    for (todo_list, file_path) in todo_lists.iter_mut().zip(file_paths) {
+        if todo_list.is_dirty && save_or_report(todo_list, file_path, console) {
+            saved += 1;
         }
     }
+    saved
 }
 
-fn get_color_text(
-    color: ConsoleForegroundColors,
-    background_color: ConsoleBackgroundColors,
-    text: &str,
-) -> String {
-    if background_color == ConsoleBackgroundColors::None {
-        return format!("\x1b[{}m{}\x1b[0m", color as u8, text);
-    } else {
-        format!(
-            "\x1b[{};{}m{}\x1b[0m",
-            color as u8, background_color as u8, text
-        )
-    }
-}
-
-fn get_type_from_string(text: &str) -> TaskType {
-    if text.starts_with("[+]") {
-        TaskType::Doing
-    } else if text.starts_with("[X]") {
-        TaskType::Done
-    } else if text.starts_with("[-]") {
-        TaskType::Rejected
-    } else if text.starts_with("[ ]") {
-        TaskType::Todo
+// give every selected task the status after (or before) the one of the task under the cursor
+fn change_selected_types(todo_list: &mut TodoList, console: &mut Console, forward: bool) {
+    let (Some((first, last)), Some(index)) = (
+        console.selection(),
+        todo_list.task_index(console.cursor_position.1),
+    ) else {
+        return;
+    };
+    let current = todo_list.tasks[index].task_type;
+    let task_type = if forward {
+        current.next()
     } else {
-        TaskType::NotDefined
-    }
+        current.prev()
+    };
+    set_selected_types(todo_list, console, (first, last), index, task_type);
 }
 
-fn type_to_string(task_type: TaskType) -> String {
-    match task_type {
-        TaskType::Todo => "[ ]".to_string(),
-        TaskType::Doing => "[+]".to_string(),
-        TaskType::Done => "[X]".to_string(),
-        TaskType::Rejected => "[-]".to_string(),
-        TaskType::NotDefined => "[ ]".to_string(),
-    }
+// give every task in the rows the status, the cursor stays on the task with the index
+fn set_selected_types(
+    todo_list: &mut TodoList,
+    console: &mut Console,
+    (first, last): (u16, u16),
+    index: usize,
+    task_type: TaskType,
+) {
+    // collected before changing anything, a new status can move the tasks in the view
+    let indices: Vec<usize> = (first..=last)
+        .filter_map(|row| todo_list.task_index(row))
+        .collect();
+    let changed = todo_list.set_types(&indices, task_type);
+    console.cursor_position.1 = todo_list.task_row(index);
+    console.selection_anchor = None;
+    console.message = Some(format!("Changed {} tasks to {}", changed, task_type));
 }
 
-struct Task {
-    task_type: TaskType,
-    text: String,
+// what the list view is in the middle of, Esc backs out of it one step at a time and
+// quits from the plain list; typing in a prompt never gets here, the prompt takes Esc itself
+enum Mode {
+    List,
+    // Right was pressed, the status is saved with Left
+    Cycling,
+    // z was pressed, Left and Right scroll the selected task
+    Scrolling,
+    // a range was started with V
+    Selecting,
+    // a search, a tag filter or the today view hides some tasks
+    Filtering,
 }
 
-impl Task {
-    fn change_type(&mut self) {
-        self.task_type = self.task_type.next();
-        self.text
-            .replace_range(0..3, &type_to_string(self.task_type));
+impl Mode {
+    fn of(todo_list: &TodoList, console: &Console) -> Mode {
+        if todo_list.is_editing {
+            Mode::Cycling
+        } else if console.horizontal_scroll.is_some() {
+            Mode::Scrolling
+        } else if console.selection_anchor.is_some() {
+            Mode::Selecting
+        } else if !todo_list.filter.is_empty()
+            || !todo_list.tag_filter.is_empty()
+            || todo_list.is_showing_today()
+        {
+            Mode::Filtering
+        } else {
+            Mode::List
+        }
     }
 }
 
-struct TodoList {
-    tasks: Vec<Task>,
-    is_editing: bool,
+// Left and Right scroll the selected task instead of changing its status while scrolling
+fn is_scroll_key(console: &Console, keymap: &KeyMap, event: &Event) -> bool {
+    console.horizontal_scroll.is_some()
+        && matches!(
+            event,
+            Event::Key(key) if matches!(
+                keymap.action(key),
+                Some(Action::CycleStatus | Action::StopCycling)
+            )
+        )
 }
 
-struct Console {
-    cursor_position: (u16, u16),
+// ask whether to save when something changed, returns whether to save on exit or None
+// to keep going
+fn confirm_quit(
+    todo_lists: &[TodoList],
+    events: &mut InputEvents,
+    console: &Console,
+) -> Option<bool> {
+    if !todo_lists.iter().any(|todo_list| todo_list.is_dirty) {
+        return Some(true);
+    }
+    let (_, height) = console.terminal_size;
+    match read_choice(events, "Save before quitting? (y/n/c)", height - 1) {
+        Some('y') => Some(true),
+        Some('n') => Some(false),
+        _ => None,
+    }
 }
 
-impl Console {
-    fn new() -> Self {
-        Self {
-            cursor_position: (1, 1),
-        }
-    }
+// subcommands that change the file without starting the interactive mode
+const COMMANDS: &[&str] = &["add", "done", "json", "export", "import", "fix"];
 
-    fn move_cursor(&mut self, direction: Direction) {
-        let mut stdout = stdout().into_raw_mode().unwrap();
-        match direction {
-            Direction::Up => {
-                if self.cursor_position.1 > 1 {
-                    self.cursor_position.1 -= 1;
-                }
-            }
-            Direction::Down => {
-                self.cursor_position.1 += 1;
+// run a subcommand on the file and print the resulting list, returns the exit code
+fn run_command(file_path: &str, format: FileFormat, command: &str, args: &[String]) -> i32 {
+    let mut todo_list = TodoList::new();
+    todo_list.format = format;
+    if let Err(error) = todo_list.load(file_path) {
+        eprintln!("Could not open {}: {}", file_path, error);
+        return 1;
+    }
+    match command {
+        "add" => {
+            let text = args.join(" ");
+            if text.trim().is_empty() {
+                eprintln!("Usage: todo <FILE> add <TEXT>");
+                return 2;
             }
-            Direction::Left => {
-                if self.cursor_position.0 > 1 {
-                    self.cursor_position.0 -= 1;
+            todo_list.add(
+                compose_task_text(TaskType::Todo, Priority::default(), text.trim()).as_str(),
+                TaskType::Todo,
+            );
+        }
+        "done" => {
+            let number = match args.first().and_then(|arg| arg.parse::<usize>().ok()) {
+                Some(number) => number,
+                None => {
+                    eprintln!("Usage: todo <FILE> done <NUMBER>");
+                    return 2;
+                }
+            };
+            let index = match task_numbers(&todo_list).get(number.wrapping_sub(1)) {
+                Some(&index) => index,
+                None => {
+                    eprintln!("There is no task number {} in {}", number, file_path);
+                    return 1;
                 }
+            };
+            todo_list.set_type(index, TaskType::Done);
+        }
+        // read-only, the tasks are printed and the file is not saved
+        "json" => {
+            print!("{}", tasks_to_status_json(&todo_list.tasks));
+            return 0;
+        }
+        // the list itself is left untouched, only the output file is written
+        "export" => {
+            let out_path = match args {
+                [format, out_path] if format == "md" => out_path,
+                _ => {
+                    eprintln!("Usage: todo <FILE> export md <OUT>");
+                    return 2;
+                }
+            };
+            if let Err(error) = std::fs::write(out_path, to_markdown(&todo_list)) {
+                eprintln!("Could not write {}: {}", out_path, error);
+                return 1;
             }
-            Direction::Right => {
-                if self.cursor_position.0 < 10 {
-                    self.cursor_position.0 += 1;
+            return 0;
+        }
+        "import" => {
+            let in_path = match args {
+                [format, in_path] if format == "md" => in_path,
+                _ => {
+                    eprintln!("Usage: todo <FILE> import md <IN>");
+                    return 2;
                 }
+            };
+            let markdown = match std::fs::read_to_string(in_path) {
+                Ok(markdown) => markdown,
+                Err(error) => {
+                    eprintln!("Could not open {}: {}", in_path, error);
+                    return 1;
+                }
+            };
+            for task in from_markdown(&markdown) {
+                todo_list.add(&task.text, task.task_type);
             }
         }
+        "fix" => {
+            todo_list.fix_undefined();
+        }
+        _ => {
+            eprintln!("Unknown command {}", command);
+            return 2;
+        }
+    }
+    if let Err(error) = todo_list.save(file_path) {
+        eprintln!("Could not save {}: {}", file_path, error);
+        return 1;
+    }
 
-        stdout.flush().unwrap();
-        write!(
-            stdout,
-            "{}({}, {})",
-            termion::cursor::Goto(25, 25),
-            self.cursor_position.0,
-            self.cursor_position.1
-        )
-        .unwrap();
-        write!(
-            stdout,
-            "{}",
-            termion::cursor::Goto(self.cursor_position.0, self.cursor_position.1)
-        )
-        .unwrap();
+    let numbers = task_numbers(&todo_list);
+    for (i, task) in todo_list.tasks.iter().enumerate() {
+        match numbers.iter().position(|&index| index == i) {
+            Some(number) => println!("{:>3} {}", number + 1, task.line()),
+            None => println!("    {}", task.line()),
+        }
     }
+    0
 }
 
-impl TodoList {
-    fn new() -> Self {
-        Self {
-            tasks: Vec::new(),
-            is_editing: false,
-        }
+// indices of the tasks in file order, comment lines are not numbered
+fn task_numbers(todo_list: &TodoList) -> Vec<usize> {
+    (0..todo_list.tasks.len())
+        .filter(|&i| !todo_list.tasks[i].is_comment())
+        .collect()
+}
+
+// the keys are the ones of the config file
+fn print_usage(keymap: &KeyMap) {
+    println!("Usage: todo <FILE>...");
+    println!("       todo <FILE> add <TEXT>");
+    println!("       todo <FILE> done <NUMBER>");
+    println!("       todo <FILE> json");
+    println!("       todo <FILE> export md <OUT>");
+    println!("       todo <FILE> import md <IN>");
+    println!("       todo <FILE> fix");
+    println!();
+    println!("Opens each FILE (created if missing) as an interactive todo list in its own tab.");
+    println!("Without a FILE the one in TODO_FILE is used, a FILE given on the command line wins.");
+    println!("With add or done the task is added or marked done (numbered from 1 in file order),");
+    println!("the resulting list is printed and the program exits.");
+    println!("Everything after -- is text or a path, e.g. todo list.txt add -- -5 degrees.");
+    println!("With json the tasks are printed as a JSON array of objects with the status keyword");
+    println!("and the description of each task.");
+    println!("With export md the list is written to OUT as Markdown checkboxes, import md appends");
+    println!("the checkbox items of IN to the list. With fix every line without a status marker");
+    println!("becomes a todo task.");
+    println!("Files ending in .json are stored as JSON, anything else as plain text.");
+    println!("--format tsv stores a status keyword (todo, doing, done or rejected), a tab and the");
+    println!("text on each line instead, --format json stores JSON whatever the file is called.");
+    println!("Blank lines and lines starting with # are kept in place and are not tasks.");
+    println!("Lines starting with ---, e.g. --- Groceries ---, are drawn in bold as headers and");
+    println!("the tasks below them are sorted on their own, under their header.");
+    println!(
+        "Tasks indented by two spaces or a tab are subtasks of the task above them, completing"
+    );
+    println!("a task completes its subtasks and a task is completed once all its subtasks are.");
+    println!("New tasks get a created: date and done tasks a completed: date, both shown on the");
+    println!("status bar for the selected task.");
+    println!("Done tasks with @daily in their text are reset to todo on the next day.");
+    println!("Without a terminal, e.g. in a pipe, the lists are printed as with --print.");
+    println!("The cursor row of each FILE is remembered in a .FILE.cursor file next to it.");
+    println!();
+    println!("Colors can be changed with TODO_COLOR_TODO, TODO_COLOR_DOING, TODO_COLOR_DONE,");
+    println!(
+        "TODO_COLOR_REJECTED and TODO_COLOR_TAG, e.g. TODO_COLOR_DONE=cyan (black, red, green,"
+    );
+    println!("yellow, blue, magenta, cyan, white or their bright- variants).");
+    println!("The markers can be drawn differently with TODO_GLYPH_TODO, TODO_GLYPH_DOING,");
+    println!(
+        "TODO_GLYPH_DONE and TODO_GLYPH_REJECTED, e.g. TODO_GLYPH_DONE=✓, the file keeps them."
+    );
+    println!("The order of the statuses can be changed with TODO_CYCLE, e.g. TODO_CYCLE=todo,doing,done.");
+    println!(
+        "Changed lists are saved every {} seconds, TODO_AUTOSAVE sets the seconds, 0 turns it off.",
+        AUTOSAVE_SECONDS
+    );
+    println!("All of these and the sorting (status, open, text or insertion) can also be set in");
+    println!(
+        "~/.config/todo-rust/config.toml, e.g. sort = \"open\" or done = \"cyan\" under [colors],"
+    );
+    println!("the variables and the options win over it.");
+    println!("Keys are remapped under [keys] by the action name, e.g. cycle-status = \"space l\"");
+    println!("or undo = \"ctrl-z\". The actions are:");
+    let names: Vec<&str> = ACTION_NAMES.iter().map(|&(name, _)| name).collect();
+    for line in names.chunks(8) {
+        println!("  {}", line.join(" "));
+    }
+    println!();
+    println!("Options:");
+    println!("  -h, --help     print this help and exit");
+    println!("  -V, --version  print the version and exit");
+    println!("  --debug        show the cursor coordinates above the status bar");
+    println!("  --no-sort      show the tasks in file order instead of sorted by status");
+    println!("  --read-only    browse without changing or saving the files");
+    println!("  --format FMT   store the files as text, json or tsv");
+    println!("  --log          append every status change to FILE.log");
+    println!("  --print        print the lists in color and exit, e.g. to pipe into less -R");
+    println!("  --no-color     draw without colors, also set by the NO_COLOR variable");
+    println!();
+    println!("Keys:");
+    let help = keymap.help();
+    let width = help
+        .iter()
+        .map(|(keys, _)| keys.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (keys, description) in help {
+        println!("  {:<width$} {}", keys, description);
     }
+}
 
-    fn load(&mut self, file_path: &str) {
-        let file = match File::open(file_path) {
-            Ok(file) => file,
-            Err(_) => match File::create(file_path) {
-                Ok(_) => File::open(file_path).unwrap(),
-                Err(_) => {
-                    println!("Could not create file");
-                    return;
-                }
-            },
+// options without a value, --format is the only one that takes the word after it
+const OPTIONS: &[&str] = &[
+    "-h",
+    "--help",
+    "-V",
+    "--version",
+    "--debug",
+    "--no-sort",
+    "--read-only",
+    "--log",
+    "--print",
+    "--no-color",
+];
+
+// the command line split into the options and the other words, the paths and the
+// subcommand with its arguments
+struct Arguments {
+    options: Vec<String>,
+    format: Option<String>,
+    words: Vec<String>,
+}
+
+impl Arguments {
+    fn parse(args: &[String]) -> Result<Arguments, String> {
+        let mut arguments = Arguments {
+            options: Vec::new(),
+            format: None,
+            words: Vec::new(),
         };
-        let reader = BufReader::new(file);
-        self.tasks = Vec::new();
-        for line in reader.lines() {
-            match line {
-                Ok(line) => self.add(line.as_str(), get_type_from_string(line.as_str())),
-                Err(_) => continue,
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                // everything after -- is a word, even when it starts with -
+                "--" => arguments.words.extend(args.by_ref().cloned()),
+                "--format" => {
+                    let format = args.next().ok_or("--format needs a value")?;
+                    arguments.format = Some(format.clone());
+                }
+                option if OPTIONS.contains(&option) => arguments.options.push(arg.clone()),
+                // the text of a subcommand may start with -, e.g. add -5 degrees
+                _ if arg.starts_with('-') && !arguments.has_command() => {
+                    return Err(format!("Unknown option {}", arg))
+                }
+                _ => arguments.words.push(arg.clone()),
             }
         }
+        Ok(arguments)
     }
 
-    fn add(&mut self, text: &str, task_type: TaskType) {
-        let new_task = Task {
-            task_type,
-            text: text.to_string(),
-        };
-        self.tasks.push(new_task);
+    fn has(&self, option: &str) -> bool {
+        self.options.iter().any(|own| own == option)
     }
 
-    fn save(&mut self, file_path: &str) {
-        let file = File::create(file_path).unwrap();
-        let mut writer = BufWriter::new(file);
-        self.tasks.sort_by_key(|task| task.task_type);
-        for task in &self.tasks {
-            let mut new_line = task.text.to_owned();
-            new_line.push_str("\n");
-            writer.write(new_line.as_bytes()).unwrap();
+    fn has_command(&self) -> bool {
+        self.words
+            .iter()
+            .any(|word| COMMANDS.contains(&word.as_str()))
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let arguments = match Arguments::parse(&args) {
+        Ok(arguments) => arguments,
+        Err(error) => {
+            eprintln!("{}", error);
+            eprintln!("Run with --help for usage");
+            std::process::exit(2);
         }
+    };
+    if arguments.has("-h") || arguments.has("--help") {
+        print_usage(&Config::load().keymap);
+        return;
+    }
+    if arguments.has("-V") || arguments.has("--version") {
+        println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+        return;
     }
 
-    fn print(&mut self, console: &mut Console) {
-        let mut stdout = stdout().into_raw_mode().unwrap();
-        for (i, task) in self.tasks.iter().enumerate() {
-            let mut x_position = 1;
-            if self.is_editing && console.cursor_position.1 == (i + 1) as u16 {
-                x_position = 3;
-            }
-            write!(
-                stdout,
-                "{}{}",
-                termion::cursor::Goto(x_position, i as u16 + 1),
-                termion::clear::CurrentLine
-            )
-            .unwrap();
-            stdout.flush().unwrap();
-            let background_color = if console.cursor_position.1 == (i + 1) as u16 {
-                ConsoleBackgroundColors::White
-            } else {
-                ConsoleBackgroundColors::None
-            };
-            let mut text = task.text.to_owned();
-            if self.is_editing && console.cursor_position.1 == (i + 1) as u16 {
-                text.push_str(
-                    format!(
-                        " (Current: {}, Next: {})",
-                        task.task_type,
-                        task.task_type.next()
-                    )
-                    .as_str(),
-                );
+    let format = match &arguments.format {
+        None => FileFormat::Text,
+        Some(name) => match FileFormat::from_name(name) {
+            Some(format) => format,
+            None => {
+                eprintln!("Unknown --format, expected text, json or tsv");
+                std::process::exit(2);
             }
+        },
+    };
+    let mut file_paths = arguments.words.clone();
+    // a path on the command line wins over TODO_FILE, which also works with the subcommands
+    let starts_with_command = file_paths
+        .first()
+        .is_some_and(|first| COMMANDS.contains(&first.as_str()));
+    if file_paths.is_empty() || starts_with_command {
+        if let Some(file_path) = env::var("TODO_FILE").ok().filter(|path| !path.is_empty()) {
+            file_paths.insert(0, file_path);
+        }
+    }
+    if file_paths.is_empty() {
+        eprintln!("Please provide a path to the file");
+        eprintln!("Run with --help for usage");
+        std::process::exit(1);
+    }
 
-            if task.task_type == TaskType::Done {
-                println!(
-                    "{}",
-                    get_color_text(
-                        ConsoleForegroundColors::Green,
-                        background_color,
-                        text.as_str()
-                    )
-                );
-            } else if task.task_type == TaskType::Todo {
-                println!(
-                    "{}",
-                    get_color_text(
-                        ConsoleForegroundColors::Blue,
-                        background_color,
-                        text.as_str()
-                    )
-                );
-            } else if task.task_type == TaskType::Doing {
-                println!(
-                    "{}",
-                    get_color_text(
-                        ConsoleForegroundColors::Magenta,
-                        background_color,
-                        text.as_str()
-                    )
-                );
-            } else if task.task_type == TaskType::Rejected {
-                println!(
-                    "{}",
-                    get_color_text(
-                        ConsoleForegroundColors::Red,
-                        background_color,
-                        text.as_str()
-                    )
-                );
-            }
+    if let [file_path, command, command_args @ ..] = file_paths.as_slice() {
+        if COMMANDS.contains(&command.as_str()) {
+            std::process::exit(run_command(file_path, format, command, command_args));
         }
     }
-}
+    // every other word opens another tab, even without an extension like `todo list.txt notes`,
+    // only a subcommand further on is refused as it belongs right after the first path
+    if let Some(word) = file_paths
+        .iter()
+        .skip(1)
+        .find(|word| COMMANDS.contains(&word.as_str()))
+    {
+        eprintln!("The {} command goes right after the path", word);
+        eprintln!("Run with --help for usage");
+        std::process::exit(2);
+    }
 
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
+    let mut console = Console::new();
+    console.debug = arguments.has("--debug");
+    if arguments.has("--no-color") || env::var("NO_COLOR").is_ok_and(|value| !value.is_empty()) {
+        disable_colors();
+    }
+    // the environment variables and the flags win over the config file
+    let config = Config::load();
+    if !config.warnings.is_empty() {
+        console.message = Some(config.warnings.join("; "));
+    }
+    console.theme = config.theme.with_env();
+    let keymap = config.keymap;
+    let mut cycle = config.cycle;
+    if let Ok(value) = env::var("TODO_CYCLE") {
+        match parse_status_cycle(&value) {
+            Some(env_cycle) => cycle = Some(env_cycle),
+            None => console.message = Some(format!("Ignoring the invalid TODO_CYCLE={}", value)),
+        }
+    }
+    if let Some(cycle) = cycle {
+        set_status_cycle(cycle);
+    }
+    console.read_only = arguments.has("--read-only");
+    let mut autosave_interval =
+        Some(Duration::from_secs(config.autosave_seconds)).filter(|interval| !interval.is_zero());
+    if let Ok(value) = env::var("TODO_AUTOSAVE") {
+        match value.trim().parse::<u64>() {
+            Ok(0) => autosave_interval = None,
+            Ok(seconds) => autosave_interval = Some(Duration::from_secs(seconds)),
+            Err(_) => {
+                console.message = Some(format!("Ignoring the invalid TODO_AUTOSAVE={}", value))
+            }
+        }
+    }
+    if console.read_only {
+        autosave_interval = None;
+    }
+    // saving never reorders the file, this also shows the tasks in file order
+    let sort_mode = if arguments.has("--no-sort") {
+        SortMode::Insertion
+    } else {
+        config.sort_mode
+    };
+    let log = arguments.has("--log");
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+    // the interactive mode needs a terminal on both ends, e.g. not in a pipe or in CI
+    let is_terminal = termion::is_tty(&stdin()) && termion::is_tty(&stdout());
+    let print = arguments.has("--print");
+    if !print && !is_terminal {
+        eprintln!("Not running in a terminal, printing the list instead");
+        // whatever reads the pipe would get the escape codes as text, --print keeps them
+        // for less -R
+        disable_colors();
+    }
 
-    if args.len() <= 1 {
-        println!("Please provide a path to the file");
+    // print the lists as they would be shown and leave the files alone
+    if print || !is_terminal {
+        for file_path in &file_paths {
+            let mut todo_list = TodoList::new();
+            todo_list.format = format;
+            todo_list.sort_mode = sort_mode;
+            if !Path::new(file_path).exists() {
+                eprintln!("Could not open {}: the file does not exist", file_path);
+                std::process::exit(1);
+            }
+            if let Err(error) = todo_list.load(file_path) {
+                eprintln!("Could not open {}: {}", file_path, error);
+                std::process::exit(1);
+            }
+            if file_paths.len() > 1 {
+                println!("{}:", file_path);
+            }
+            print!("{}", todo_list.to_colored_text(&console.theme));
+        }
         return;
     }
 
-    let file_path = &args[1];
-    let mut console = Console::new();
-
-    let mut todo_list = TodoList::new();
-    todo_list.load(file_path);
-    todo_list.print(&mut console);
-    // todo_list.add("Buy milk");
-    todo_list.save(file_path);
+    let mut todo_lists: Vec<TodoList> = Vec::new();
+    for file_path in &file_paths {
+        let mut todo_list = TodoList::new();
+        todo_list.format = format;
+        todo_list.sort_mode = sort_mode;
+        // loading creates missing files, which a read-only run must not do
+        if console.read_only && !Path::new(file_path).exists() {
+            eprintln!("Could not open {}: the file does not exist", file_path);
+            std::process::exit(1);
+        }
+        if let Err(error) = todo_list.load(file_path) {
+            eprintln!("Could not open {}: {}", file_path, error);
+            std::process::exit(1);
+        }
+        if console.read_only {
+            todo_lists.push(todo_list);
+            continue;
+        }
+        if log {
+            todo_list.log_path = Some(log_path(file_path));
+        }
+        if let Err(error) = todo_list.save(file_path) {
+            eprintln!("Could not save {}: {}", file_path, error);
+            std::process::exit(1);
+        }
+        todo_lists.push(todo_list);
+    }
+    // cursor row of every tab, restored when switching back to it
+    let mut cursor_rows: Vec<u16> = todo_lists
+        .iter()
+        .zip(&file_paths)
+        .map(|(todo_list, file_path)| {
+            let tasks_count = todo_list.view_order().len().max(1);
+            load_cursor(file_path).min(tasks_count as u16)
+        })
+        .collect();
+    let mut active = 0;
+    console.cursor_position.1 = cursor_rows[active];
+    let mut save_on_exit = !console.read_only;
+    console.header = Some(tab_header(&file_paths, active, console.read_only));
 
-    let stdin = stdin();
-    let mut stdout = stdout().into_raw_mode().unwrap();
+    let mut events = InputEvents::from_stdin();
+    // the only raw mode handle, everything else writes to plain stdout while it lives and
+    // the terminal is restored when it is dropped; terminals without mouse reporting simply
+    // never send mouse events
+    let mut stdout = MouseTerminal::from(stdout().into_raw_mode().unwrap());
     write!(
         stdout,
         "{}{}{}",
@@ -366,38 +602,658 @@ fn main() {
     )
     .unwrap();
     stdout.flush().unwrap();
+    todo_lists[active].print(&mut console);
 
-    for c in stdin.keys() {
-        match c.unwrap() {
-            Key::Char('q') => break,
-            Key::Up => {
-                if todo_list.is_editing == false {
-                    console.move_cursor(Direction::Up);
-                }
-            }
-            Key::Down => {
-                if todo_list.is_editing == false {
-                    console.move_cursor(Direction::Down)
+    let mut read_error = None;
+    let mut next_autosave = autosave_interval.map(|interval| Instant::now() + interval);
+    loop {
+        // wake up now and then without a keypress to notice a resize
+        let timeout = match next_autosave {
+            Some(deadline) => deadline
+                .saturating_duration_since(Instant::now())
+                .min(RESIZE_POLL),
+            None => RESIZE_POLL,
+        };
+        let event = match events.next_timeout(timeout) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => {
+                if next_autosave.is_some_and(|deadline| Instant::now() >= deadline) {
+                    if autosave(&mut todo_lists, &file_paths, &mut console) > 0
+                        && console.message.is_none()
+                    {
+                        console.notice = Some(SAVED.to_string());
+                    }
+                    next_autosave = autosave_interval.map(|interval| Instant::now() + interval);
+                    todo_lists[active].print(&mut console);
+                } else if console.is_resized() {
+                    todo_lists[active].print(&mut console);
                 }
+                continue;
             }
-            Key::Right => {
-                todo_list.tasks[(console.cursor_position.1 - 1) as usize].change_type();
-                todo_list.is_editing = true;
-
-                todo_list.print(&mut console);
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        console.message = None;
+        console.notice = None;
+        let todo_list = &mut todo_lists[active];
+        let file_path = &file_paths[active];
+        // the terminal went away, leave the loop so the lists are still saved
+        let event = match event {
+            Ok(event) => event,
+            Err(error) => {
+                read_error = Some(error);
+                break;
             }
-            Key::Left => {
-                todo_list.is_editing = false;
-                todo_list.save(file_path);
-                todo_list.print(&mut console);
+        };
+        if console.read_only
+            && is_mutating(&event, &keymap)
+            && !is_scroll_key(&console, &keymap, &event)
+        {
+            console.message = Some("The list is read-only".to_string());
+            todo_list.print(&mut console);
+            continue;
+        }
+        match event {
+            Event::Key(key) => match keymap.action(&key) {
+                Some(Action::Quit) => {
+                    if let Some(save) = confirm_quit(&todo_lists, &mut events, &console) {
+                        save_on_exit &= save;
+                        break;
+                    }
+                }
+                Some(action @ (Action::NextFile | Action::PreviousFile))
+                    if file_paths.len() > 1 =>
+                {
+                    todo_list.is_editing = false;
+                    save_or_report(todo_list, file_path, &mut console);
+                    cursor_rows[active] = console.cursor_position.1;
+                    console.selection_anchor = None;
+                    active = if action == Action::PreviousFile {
+                        (active + file_paths.len() - 1) % file_paths.len()
+                    } else {
+                        (active + 1) % file_paths.len()
+                    };
+                    console.cursor_position.1 = cursor_rows[active];
+                    console.header = Some(tab_header(&file_paths, active, console.read_only));
+                }
+                Some(Action::Add) if !todo_list.is_editing => {
+                    let (_, height) = console.terminal_size;
+                    let row = (console.screen_row(todo_list.view_order().len() as u16) + 2)
+                        .min(height - 1);
+                    if let Some(text) =
+                        read_input(&mut events, "New task: ", "", row, None, &mut |_| {})
+                            .filter(|text| !text.trim().is_empty())
+                    {
+                        todo_list.add(
+                            compose_task_text(TaskType::Todo, Priority::default(), text.trim())
+                                .as_str(),
+                            TaskType::Todo,
+                        );
+                        console.cursor_position.1 = todo_list.task_row(todo_list.tasks.len() - 1);
+                    }
+                }
+                Some(action @ (Action::InsertBelow | Action::InsertAbove))
+                    if !todo_list.is_editing =>
+                {
+                    let (_, height) = console.terminal_size;
+                    let row = console.cursor_position.1;
+                    // the new task goes next to the selected one at the same depth, below its subtasks
+                    let (position, depth, screen_row) = match todo_list.task_index(row) {
+                        Some(index) if action == Action::InsertAbove => {
+                            (index, todo_list.tasks[index].depth, console.screen_row(row))
+                        }
+                        Some(index) => (
+                            index + todo_list.children(index).len() + 1,
+                            todo_list.tasks[index].depth,
+                            console.screen_row(row) + 1,
+                        ),
+                        None => (todo_list.tasks.len(), 0, console.screen_row(row)),
+                    };
+                    if let Some(text) = read_input(
+                        &mut events,
+                        "New task: ",
+                        "",
+                        screen_row.min(height - 1),
+                        None,
+                        &mut |_| {},
+                    )
+                    .filter(|text| !text.trim().is_empty())
+                    {
+                        let text =
+                            compose_task_text(TaskType::Todo, Priority::default(), text.trim());
+                        todo_list.insert(
+                            position,
+                            &format!("{}{}", INDENT.repeat(depth), text),
+                            TaskType::Todo,
+                        );
+                        console.cursor_position.1 = todo_list.task_row(position);
+                    }
+                }
+                Some(Action::Notes) if !todo_list.is_editing => {
+                    if let Some(index) = todo_list.task_index(console.cursor_position.1) {
+                        let task = &todo_list.tasks[index];
+                        if let Some(notes) = edit_notes(&mut events, &task.text, &task.notes) {
+                            if console.read_only {
+                                console.message = Some("The list is read-only".to_string());
+                            } else {
+                                todo_list.set_notes(index, &notes);
+                            }
+                        }
+                    }
+                }
+                Some(Action::Edit) if !todo_list.is_editing => {
+                    if let Some(index) = todo_list.task_index(console.cursor_position.1) {
+                        let task = &todo_list.tasks[index];
+                        let label = if task.task_type == TaskType::NotDefined {
+                            String::new()
+                        } else {
+                            compose_task_text(task.task_type, task.priority, "")
+                        };
+                        let color = console.theme.color(task.task_type);
+                        let original = get_description(&task.text).to_string();
+                        match edit_line(
+                            &mut events,
+                            &label,
+                            &original,
+                            console.screen_row(console.cursor_position.1),
+                            Some(color),
+                        ) {
+                            LineEdit::Submitted(description) if !description.trim().is_empty() => {
+                                todo_list.edit_task(index, description.trim());
+                            }
+                            LineEdit::Split(description, at) => {
+                                // the offset counts the leading spaces, which are not stored
+                                let leading = description
+                                    .chars()
+                                    .take_while(|c| c.is_whitespace())
+                                    .count();
+                                if description.trim_start() != original {
+                                    todo_list.edit_task(index, description.trim_start());
+                                }
+                                match todo_list.split_task(index, at.saturating_sub(leading)) {
+                                    Some(position) => {
+                                        console.cursor_position.1 = todo_list.task_row(position)
+                                    }
+                                    None => {
+                                        console.message = Some(
+                                            "Nothing to split off at the start or the end"
+                                                .to_string(),
+                                        )
+                                    }
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Some(Action::DueDate) if !todo_list.is_editing => {
+                    if let Some(index) = todo_list.task_index(console.cursor_position.1) {
+                        let (_, height) = console.terminal_size;
+                        let mut label = "Due date (YYYY-MM-DD, empty to clear): ";
+                        let mut initial = todo_list.tasks[index]
+                            .due
+                            .map(|due| due.to_string())
+                            .unwrap_or_default();
+                        while let Some(input) =
+                            read_input(&mut events, label, &initial, height - 1, None, &mut |_| {})
+                        {
+                            if input.trim().is_empty() {
+                                todo_list.set_due_date(index, None);
+                                break;
+                            }
+                            if let Some(due) = Date::parse(&input) {
+                                todo_list.set_due_date(index, Some(due));
+                                break;
+                            }
+                            label = "Invalid date, use YYYY-MM-DD: ";
+                            initial = input;
+                        }
+                    }
+                }
+                Some(Action::Color) if !todo_list.is_editing => {
+                    if let Some(index) = todo_list.task_index(console.cursor_position.1) {
+                        let color = next_override_color(todo_list.tasks[index].color_override);
+                        todo_list.set_color_override(index, color);
+                    }
+                }
+                Some(Action::ClearCompleted) if !todo_list.is_editing => {
+                    let counts = todo_list.counts();
+                    let completed = counts.done + counts.rejected;
+                    if completed == 0 {
+                        console.message = Some("No completed tasks to clear".to_string());
+                    } else {
+                        let (_, height) = console.terminal_size;
+                        let question =
+                            format!("Remove {} done and rejected tasks? (y/n)", completed);
+                        if read_choice(&mut events, &question, height - 1) == Some('y') {
+                            let removed = todo_list.clear_completed();
+                            console.clamp_cursor(todo_list.view_order().len());
+                            save_or_report(todo_list, file_path, &mut console);
+                            if console.message.is_none() {
+                                console.message = Some(format!("Removed {} tasks", removed));
+                            }
+                        }
+                    }
+                }
+                Some(Action::MergeDuplicates) if !todo_list.is_editing => {
+                    let duplicates = todo_list.duplicates().len();
+                    if duplicates == 0 {
+                        console.message = Some("No duplicate tasks".to_string());
+                    } else {
+                        let (_, height) = console.terminal_size;
+                        let question = format!("Merge {} duplicate tasks? (y/n)", duplicates);
+                        if read_choice(&mut events, &question, height - 1) == Some('y') {
+                            let removed = todo_list.dedupe();
+                            console.clamp_cursor(todo_list.view_order().len());
+                            save_or_report(todo_list, file_path, &mut console);
+                            if console.message.is_none() {
+                                console.message = Some(format!("Removed {} duplicates", removed));
+                            }
+                        }
+                    }
+                }
+                Some(Action::Archive) if !todo_list.is_editing => {
+                    let counts = todo_list.counts();
+                    let completed = counts.done + counts.rejected;
+                    let archive = archive_path(file_path);
+                    if completed == 0 {
+                        console.message = Some("No completed tasks to archive".to_string());
+                    } else {
+                        let (_, height) = console.terminal_size;
+                        let question = format!(
+                            "Move {} done and rejected tasks to {}? (y/n)",
+                            completed,
+                            archive.display()
+                        );
+                        if read_choice(&mut events, &question, height - 1) == Some('y') {
+                            match todo_list.archive_completed(&archive) {
+                                Ok(archived) => {
+                                    console.clamp_cursor(todo_list.view_order().len());
+                                    save_or_report(todo_list, file_path, &mut console);
+                                    if console.message.is_none() {
+                                        console.message =
+                                            Some(format!("Archived {} tasks", archived));
+                                    }
+                                }
+                                Err(error) => {
+                                    console.message = Some(format!("Archive failed: {}", error))
+                                }
+                            }
+                        }
+                    }
+                }
+                Some(Action::Unarchive) if !todo_list.is_editing => {
+                    let archive = archive_path(file_path);
+                    match archived_tasks(&archive) {
+                        // a list that was never archived has no archive file yet
+                        Err(error) if error.kind() != io::ErrorKind::NotFound => {
+                            console.message = Some(format!("Restore failed: {}", error))
+                        }
+                        Ok(archived) if !archived.is_empty() => {
+                            let lines: Vec<String> =
+                                archived.iter().map(|task| task.line()).collect();
+                            let title = format!("Restore from {} (Enter, Esc)", archive.display());
+                            if let Some(picked) =
+                                pick_line(&mut events, &title, &lines, console.terminal_size)
+                            {
+                                match todo_list.unarchive(&archive, picked) {
+                                    Ok(Some(index)) => {
+                                        console.cursor_position.1 = todo_list.task_row(index);
+                                        save_or_report(todo_list, file_path, &mut console);
+                                        if console.message.is_none() {
+                                            console.message = Some("Restored 1 task".to_string());
+                                        }
+                                    }
+                                    Ok(None) => {}
+                                    Err(error) => {
+                                        console.message = Some(format!("Restore failed: {}", error))
+                                    }
+                                }
+                            }
+                        }
+                        _ => {
+                            console.message =
+                                Some(format!("Nothing archived in {}", archive.display()))
+                        }
+                    }
+                }
+                Some(Action::Duplicate) if !todo_list.is_editing => {
+                    if let Some(index) = todo_list
+                        .task_index(console.cursor_position.1)
+                        .and_then(|index| todo_list.duplicate_task(index))
+                    {
+                        console.cursor_position.1 = todo_list.task_row(index);
+                    }
+                }
+                Some(Action::MarkAllDone) if !todo_list.is_editing => {
+                    let selected = todo_list.task_index(console.cursor_position.1);
+                    let marked = todo_list.mark_all(TaskType::Done);
+                    if let Some(index) = selected {
+                        console.cursor_position.1 = todo_list.task_row(index);
+                    }
+                    console.message = Some(format!("Marked {} tasks done", marked));
+                    save_or_report(todo_list, file_path, &mut console);
+                }
+                Some(Action::Fix) if !todo_list.is_editing => {
+                    let fixed = todo_list.fix_undefined();
+                    console.message = Some(format!("Fixed {} tasks without a status", fixed));
+                    save_or_report(todo_list, file_path, &mut console);
+                }
+                Some(Action::MergeNext) if !todo_list.is_editing => {
+                    if let Some(index) = todo_list
+                        .task_index(console.cursor_position.1)
+                        .and_then(|index| todo_list.merge_with_next(index))
+                    {
+                        console.cursor_position.1 = todo_list.task_row(index);
+                        save_or_report(todo_list, file_path, &mut console);
+                    }
+                }
+                Some(Action::Delete) if !todo_list.is_editing => {
+                    if let Some(index) = todo_list.task_index(console.cursor_position.1) {
+                        todo_list.delete_task(index);
+                        console.clamp_cursor(todo_list.view_order().len());
+                        save_or_report(todo_list, file_path, &mut console);
+                    }
+                }
+                Some(Action::Help) => show_help(&mut events, &keymap, console.terminal_size),
+                Some(Action::Search) if !todo_list.is_editing => {
+                    let (_, height) = console.terminal_size;
+                    let initial = todo_list.filter.clone();
+                    let query =
+                        read_input(&mut events, "/", &initial, height - 1, None, &mut |query| {
+                            todo_list.filter = query.to_string();
+                            todo_list.print(&mut console);
+                        });
+                    todo_list.filter = query.unwrap_or_default();
+                    console.cursor_position.1 = 1;
+                }
+                Some(Action::TagFilter) if !todo_list.is_editing => {
+                    let (_, height) = console.terminal_size;
+                    let initial = todo_list.tag_filter.clone();
+                    if let Some(tag) = read_input(
+                        &mut events,
+                        "Tag: #",
+                        &initial,
+                        height - 1,
+                        None,
+                        &mut |_| {},
+                    ) {
+                        let tag = tag.trim().trim_start_matches('#');
+                        if tag.is_empty() || !todo_list.tasks_with_tag(tag).is_empty() {
+                            todo_list.tag_filter = tag.to_string();
+                            console.cursor_position.1 = 1;
+                        } else {
+                            console.message = Some(format!("No task is tagged #{}", tag));
+                        }
+                    }
+                }
+                Some(Action::Today) if !todo_list.is_editing => {
+                    todo_list.toggle_today();
+                    console.selection_anchor = None;
+                    console.cursor_position.1 = 1;
+                }
+                Some(Action::Focus) if !todo_list.is_editing => {
+                    todo_list.toggle_focus();
+                    console.selection_anchor = None;
+                    console.cursor_position.1 = 1;
+                }
+                Some(
+                    action @ (Action::ToggleTodo
+                    | Action::ToggleDoing
+                    | Action::ToggleDone
+                    | Action::ToggleRejected),
+                ) if !todo_list.is_editing => {
+                    let task_type = match action {
+                        Action::ToggleTodo => TaskType::Todo,
+                        Action::ToggleDoing => TaskType::Doing,
+                        Action::ToggleDone => TaskType::Done,
+                        _ => TaskType::Rejected,
+                    };
+                    todo_list.toggle_type_visibility(task_type);
+                    console.clamp_cursor(todo_list.view_order().len());
+                }
+                Some(Action::Select) if !todo_list.is_editing => {
+                    console.selection_anchor = match console.selection_anchor {
+                        Some(_) => None,
+                        None => Some(console.cursor_position.1),
+                    };
+                }
+                Some(Action::Back) => match Mode::of(todo_list, &console) {
+                    // Left saves the status being cycled, Esc must not quit over it
+                    Mode::Cycling => {}
+                    Mode::Scrolling => console.horizontal_scroll = None,
+                    Mode::Selecting => console.selection_anchor = None,
+                    Mode::Filtering => {
+                        todo_list.filter.clear();
+                        todo_list.tag_filter.clear();
+                        if todo_list.is_showing_today() {
+                            todo_list.toggle_today();
+                        }
+                        console.cursor_position.1 = 1;
+                    }
+                    Mode::List => {
+                        if let Some(save) = confirm_quit(&todo_lists, &mut events, &console) {
+                            save_on_exit &= save;
+                            break;
+                        }
+                    }
+                },
+                Some(action @ (Action::MoveUp | Action::MoveDown)) if !todo_list.is_editing => {
+                    let direction = if action == Action::MoveUp {
+                        Direction::Up
+                    } else {
+                        Direction::Down
+                    };
+                    if let Some(index) = todo_list
+                        .task_index(console.cursor_position.1)
+                        .and_then(|index| todo_list.move_task(index, direction))
+                    {
+                        console.cursor_position.1 = todo_list.task_row(index);
+                    }
+                }
+                Some(action @ (Action::MoveToTop | Action::MoveToBottom))
+                    if !todo_list.is_editing =>
+                {
+                    let moved = todo_list
+                        .task_index(console.cursor_position.1)
+                        .and_then(|index| match action {
+                            Action::MoveToTop => todo_list.move_to_top(index),
+                            _ => todo_list.move_to_bottom(index),
+                        });
+                    if let Some(index) = moved {
+                        console.cursor_position.1 = todo_list.task_row(index);
+                    }
+                }
+                Some(Action::Sort) if !todo_list.is_editing => {
+                    todo_list.sort_mode = todo_list.sort_mode.next();
+                }
+                Some(Action::Undo) if !todo_list.is_editing => {
+                    if let Some(index) = todo_list.undo() {
+                        console.cursor_position.1 = todo_list.task_row(index);
+                        console.clamp_cursor(todo_list.view_order().len());
+                    }
+                }
+                Some(Action::Redo) if !todo_list.is_editing => {
+                    if let Some(index) = todo_list.redo() {
+                        console.cursor_position.1 = todo_list.task_row(index);
+                        console.clamp_cursor(todo_list.view_order().len());
+                    }
+                }
+                Some(Action::Up) if !todo_list.is_editing => {
+                    todo_list.step_cursor(&mut console, Direction::Up);
+                }
+                Some(Action::Down) if !todo_list.is_editing => {
+                    todo_list.step_cursor(&mut console, Direction::Down);
+                }
+                Some(action @ (Action::PageUp | Action::PageDown)) if !todo_list.is_editing => {
+                    let direction = if action == Action::PageUp {
+                        Direction::Up
+                    } else {
+                        Direction::Down
+                    };
+                    console.page(direction, todo_list.view_order().len());
+                    console.horizontal_scroll = console.horizontal_scroll.map(|_| 0);
+                }
+                Some(Action::Jump) if !todo_list.is_editing => {
+                    let (_, height) = console.terminal_size;
+                    if let Some(input) =
+                        read_input(&mut events, "Go to: ", "", height - 1, None, &mut |_| {})
+                    {
+                        if console.jump_to(&input, &todo_list.task_rows()) {
+                            console.horizontal_scroll = console.horizontal_scroll.map(|_| 0);
+                        }
+                    }
+                }
+                Some(Action::Scroll) if !todo_list.is_editing => {
+                    console.horizontal_scroll = match console.horizontal_scroll {
+                        Some(_) => None,
+                        None => Some(0),
+                    };
+                }
+                Some(Action::StopCycling) if console.horizontal_scroll.is_some() => {
+                    console.scroll_horizontally(Direction::Left);
+                }
+                Some(Action::CycleStatus) if console.horizontal_scroll.is_some() => {
+                    console.scroll_horizontally(Direction::Right);
+                }
+                Some(
+                    action @ (Action::SetTodo
+                    | Action::SetDoing
+                    | Action::SetDone
+                    | Action::SetRejected),
+                ) => {
+                    let task_type = match action {
+                        Action::SetTodo => TaskType::Todo,
+                        Action::SetDoing => TaskType::Doing,
+                        Action::SetDone => TaskType::Done,
+                        _ => TaskType::Rejected,
+                    };
+                    if let Some(index) = todo_list.task_index(console.cursor_position.1) {
+                        match console.selection() {
+                            Some(rows) => {
+                                set_selected_types(todo_list, &mut console, rows, index, task_type)
+                            }
+                            None => {
+                                todo_list.set_type(index, task_type);
+                                console.cursor_position.1 = todo_list.task_row(index);
+                            }
+                        }
+                        todo_list.is_editing = false;
+                        save_or_report(todo_list, file_path, &mut console);
+                    }
+                }
+                Some(Action::CycleStatus) if console.selection_anchor.is_some() => {
+                    change_selected_types(todo_list, &mut console, true);
+                    save_or_report(todo_list, file_path, &mut console);
+                }
+                Some(Action::CycleStatus) => {
+                    // nothing happens when the cursor is not on a task (e.g. empty list)
+                    todo_list.cycle_at_cursor(&mut console);
+                }
+                Some(Action::CycleStatusBack) if console.selection_anchor.is_some() => {
+                    change_selected_types(todo_list, &mut console, false);
+                    save_or_report(todo_list, file_path, &mut console);
+                }
+                Some(Action::CycleStatusBack) => {
+                    if let Some(index) = todo_list.task_index(console.cursor_position.1) {
+                        let task_type = todo_list.tasks[index].task_type.prev();
+                        todo_list.set_type(index, task_type);
+                        console.cursor_position.1 = todo_list.task_row(index);
+                        todo_list.is_editing = true;
+                    }
+                }
+                Some(action @ (Action::RaisePriority | Action::LowerPriority))
+                    if !todo_list.is_editing =>
+                {
+                    if let Some(index) = todo_list.task_index(console.cursor_position.1) {
+                        let priority = todo_list.tasks[index].priority;
+                        let priority = if action == Action::RaisePriority {
+                            priority.raise()
+                        } else {
+                            priority.lower()
+                        };
+                        todo_list.set_priority(index, priority);
+                    }
+                }
+                Some(Action::StopCycling) => {
+                    // keep the highlight on the same task whatever order the view ends up in
+                    let selected = todo_list.task_index(console.cursor_position.1);
+                    todo_list.is_editing = false;
+                    if save_or_report(todo_list, file_path, &mut console) {
+                        console.notice = Some(SAVED.to_string());
+                    }
+                    if let Some(index) = selected {
+                        console.cursor_position.1 = todo_list.task_row(index);
+                    }
+                    todo_list.print(&mut console);
+                }
+                _ => {}
+            },
+            // a click selects a task, a click on the selected task or a right click cycles it
+            Event::Mouse(MouseEvent::Press(button, _, y)) => {
+                let tasks_count = todo_list.view_order().len();
+                if let Some(row) = console.view_row(y, tasks_count) {
+                    let cycle = button == MouseButton::Right
+                        || (button == MouseButton::Left && row == console.cursor_position.1);
+                    console.cursor_position.1 = row;
+                    if cycle && !console.read_only {
+                        if let Some(index) = todo_list.task_index(row) {
+                            todo_list.change_type(index);
+                            console.cursor_position.1 = todo_list.task_row(index);
+                            todo_list.is_editing = true;
+                        }
+                    }
+                }
             }
             _ => {}
         }
 
         stdout.flush().unwrap();
-        todo_list.print(&mut console);
+        todo_lists[active].print(&mut console);
     }
 
-    todo_list.save(file_path);
-    write!(stdout, "{}", termion::cursor::Show).unwrap();
+    // the summary takes the place of the status bar once the terminal is restored
+    let summaries: Vec<String> = todo_lists
+        .iter()
+        .zip(&file_paths)
+        .map(|(todo_list, file_path)| {
+            remaining_summary(&todo_list.counts(), file_path, &console.theme)
+        })
+        .collect();
+    write!(
+        stdout,
+        "{}{}{}",
+        termion::cursor::Goto(1, console.terminal_size.1),
+        termion::clear::CurrentLine,
+        termion::cursor::Show
+    )
+    .unwrap();
+    // leave raw mode before reporting errors
+    drop(stdout);
+    if let Some(error) = read_error {
+        eprintln!("Could not read the keyboard: {}", error);
+    }
+    for summary in &summaries {
+        println!("{}", summary);
+    }
+
+    if !console.read_only {
+        cursor_rows[active] = console.cursor_position.1;
+        for (file_path, &row) in file_paths.iter().zip(&cursor_rows) {
+            // losing the cursor position is not worth failing the exit over
+            let _ = save_cursor(file_path, row);
+        }
+    }
+
+    if save_on_exit {
+        let mut failed = false;
+        for (todo_list, file_path) in todo_lists.iter_mut().zip(&file_paths) {
+            if let Err(error) = todo_list.save(file_path) {
+                eprintln!("Could not save {}: {}", file_path, error);
+                failed = true;
+            }
+        }
+        if failed {
+            std::process::exit(1);
+        }
+    }
 }
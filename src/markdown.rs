@@ -0,0 +1,68 @@
+use crate::task::{
+    compose_task_text, get_description, get_display_text, Priority, Task, TaskType, INDENT,
+};
+use crate::todo_list::TodoList;
+
+// the list as Markdown checkboxes, only done tasks are checked; subtasks are nested items and
+// the descriptions lose the priority and the stamps like in the list
+pub fn to_markdown(todo_list: &TodoList) -> String {
+    let mut markdown = String::new();
+    for task in &todo_list.tasks {
+        let description = get_display_text(get_description(&task.text));
+        if !task.is_comment() {
+            markdown.push_str(&INDENT.repeat(task.depth));
+        }
+        let line = match task.task_type {
+            _ if task.is_comment() => task.text.clone(),
+            TaskType::Done => format!("- [x] {}", description),
+            TaskType::Doing => format!("- [ ] {} (doing)", description),
+            TaskType::Rejected => format!("- [ ] {} (rejected)", description),
+            TaskType::Todo | TaskType::NotDefined => format!("- [ ] {}", description),
+        };
+        markdown.push_str(&line);
+        markdown.push('\n');
+    }
+    markdown
+}
+
+// the checkbox items of a Markdown file, any other line is ignored
+pub fn from_markdown(markdown: &str) -> Vec<Task> {
+    let mut tasks = Vec::new();
+    for line in markdown.lines() {
+        let item = line.trim_start();
+        let item = match ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|bullet| item.strip_prefix(bullet))
+        {
+            Some(item) => item.trim_start(),
+            None => continue,
+        };
+        let (mut task_type, description) = if let Some(rest) = item.strip_prefix("[ ]") {
+            (TaskType::Todo, rest)
+        } else if let Some(rest) = item
+            .strip_prefix("[x]")
+            .or_else(|| item.strip_prefix("[X]"))
+        {
+            (TaskType::Done, rest)
+        } else {
+            continue;
+        };
+        let mut description = description.trim();
+        // the status suffixes written by the export
+        if task_type == TaskType::Todo {
+            if let Some(rest) = description.strip_suffix("(doing)") {
+                task_type = TaskType::Doing;
+                description = rest.trim_end();
+            } else if let Some(rest) = description.strip_suffix("(rejected)") {
+                task_type = TaskType::Rejected;
+                description = rest.trim_end();
+            }
+        }
+        if description.is_empty() {
+            continue;
+        }
+        let text = compose_task_text(task_type, Priority::default(), description);
+        tasks.push(Task::new(&text, task_type));
+    }
+    tasks
+}
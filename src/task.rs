@@ -0,0 +1,570 @@
+use core::fmt;
+use std::sync::OnceLock;
+
+use crate::color::{color_to_string, get_color_from_string, ConsoleForegroundColors};
+use crate::date::Date;
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum TaskType {
+    Todo,
+    Doing,
+    Done,
+    Rejected,
+    NotDefined,
+}
+impl fmt::Display for TaskType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+impl Copy for TaskType {}
+
+impl Clone for TaskType {
+    fn clone(&self) -> TaskType {
+        *self
+    }
+}
+impl PartialOrd for TaskType {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for TaskType {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match self {
+            TaskType::Todo => match other {
+                TaskType::Todo => std::cmp::Ordering::Equal,
+                _ => std::cmp::Ordering::Less,
+            },
+            TaskType::Doing => match other {
+                TaskType::Todo => std::cmp::Ordering::Greater,
+                TaskType::Doing => std::cmp::Ordering::Equal,
+                _ => std::cmp::Ordering::Less,
+            },
+            TaskType::Done => match other {
+                TaskType::Todo => std::cmp::Ordering::Greater,
+                TaskType::Doing => std::cmp::Ordering::Greater,
+                TaskType::Done => std::cmp::Ordering::Equal,
+                _ => std::cmp::Ordering::Less,
+            },
+            TaskType::Rejected => match other {
+                TaskType::Todo => std::cmp::Ordering::Greater,
+                TaskType::Doing => std::cmp::Ordering::Greater,
+                TaskType::Done => std::cmp::Ordering::Greater,
+                TaskType::Rejected => std::cmp::Ordering::Equal,
+                _ => std::cmp::Ordering::Less,
+            },
+            TaskType::NotDefined => match other {
+                TaskType::NotDefined => std::cmp::Ordering::Equal,
+                _ => std::cmp::Ordering::Greater,
+            },
+        }
+    }
+}
+
+// implement for task type option to get next type in order
+impl TaskType {
+    // a status left out of the cycle moves to its start
+    pub fn next(&self) -> TaskType {
+        let cycle = status_cycle();
+        match cycle.iter().position(|task_type| task_type == self) {
+            _ if *self == TaskType::NotDefined => TaskType::NotDefined,
+            Some(position) => cycle[(position + 1) % cycle.len()],
+            None => cycle[0],
+        }
+    }
+
+    // inverse of next, a status left out of the cycle moves to its end
+    pub fn prev(&self) -> TaskType {
+        let cycle = status_cycle();
+        match cycle.iter().position(|task_type| task_type == self) {
+            _ if *self == TaskType::NotDefined => TaskType::NotDefined,
+            Some(position) => cycle[(position + cycle.len() - 1) % cycle.len()],
+            None => cycle[cycle.len() - 1],
+        }
+    }
+
+    // the status as a word, for the command line and the formats that spell it out,
+    // the brackets stored in the file are read by get_type_from_string
+    pub fn from_keyword(keyword: &str) -> Option<TaskType> {
+        match keyword.trim().to_lowercase().as_str() {
+            "todo" => Some(TaskType::Todo),
+            "doing" => Some(TaskType::Doing),
+            "done" => Some(TaskType::Done),
+            "rejected" => Some(TaskType::Rejected),
+            _ => None,
+        }
+    }
+
+    // inverse of from_keyword, a line without a status has no keyword
+    pub fn as_keyword(&self) -> &'static str {
+        match self {
+            TaskType::Todo => "todo",
+            TaskType::Doing => "doing",
+            TaskType::Done => "done",
+            TaskType::Rejected => "rejected",
+            TaskType::NotDefined => "",
+        }
+    }
+}
+
+const DEFAULT_STATUS_CYCLE: [TaskType; 4] = [
+    TaskType::Todo,
+    TaskType::Doing,
+    TaskType::Done,
+    TaskType::Rejected,
+];
+
+// order in which Right cycles the statuses, set once at startup from TODO_CYCLE
+static STATUS_CYCLE: OnceLock<Vec<TaskType>> = OnceLock::new();
+
+fn status_cycle() -> &'static [TaskType] {
+    STATUS_CYCLE
+        .get()
+        .map(|cycle| cycle.as_slice())
+        .unwrap_or(&DEFAULT_STATUS_CYCLE)
+}
+
+// use the cycle instead of the default one, only the first call has an effect
+pub fn set_status_cycle(cycle: Vec<TaskType>) {
+    let _ = STATUS_CYCLE.set(cycle);
+}
+
+// a cycle like "todo,doing,done", None when a status is unknown or repeated or there are
+// fewer than two
+pub fn parse_status_cycle(text: &str) -> Option<Vec<TaskType>> {
+    let mut cycle: Vec<TaskType> = Vec::new();
+    for name in text.split(',') {
+        let task_type = TaskType::from_keyword(name)?;
+        if cycle.contains(&task_type) {
+            return None;
+        }
+        cycle.push(task_type);
+    }
+    if cycle.len() < 2 {
+        return None;
+    }
+    Some(cycle)
+}
+
+// lines without a priority marker, such as files written before priorities, are medium
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Default)]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl Priority {
+    pub fn raise(&self) -> Priority {
+        match self {
+            Priority::Low => Priority::Medium,
+            Priority::Medium | Priority::High => Priority::High,
+        }
+    }
+
+    pub fn lower(&self) -> Priority {
+        match self {
+            Priority::High => Priority::Medium,
+            Priority::Medium | Priority::Low => Priority::Low,
+        }
+    }
+
+    // high priority tasks are drawn in the bright variant of their status color
+    pub fn color(&self, color: ConsoleForegroundColors) -> ConsoleForegroundColors {
+        match self {
+            Priority::High => color.bright(),
+            _ => color,
+        }
+    }
+}
+
+// name of the tag when the word is a #tag
+pub fn get_tag(word: &str) -> Option<&str> {
+    let tag = word
+        .strip_prefix('#')?
+        .trim_end_matches(|c: char| c.is_ascii_punctuation());
+    if !tag.is_empty()
+        && tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        Some(tag)
+    } else {
+        None
+    }
+}
+
+pub fn get_tags_from_string(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .filter_map(get_tag)
+        .map(|tag| tag.to_string())
+        .collect()
+}
+
+pub fn get_type_from_string(text: &str) -> TaskType {
+    if text.starts_with("[+]") {
+        TaskType::Doing
+    } else if text.starts_with("[X]") {
+        TaskType::Done
+    } else if text.starts_with("[-]") {
+        TaskType::Rejected
+    } else if text.starts_with("[ ]") {
+        TaskType::Todo
+    } else {
+        TaskType::NotDefined
+    }
+}
+
+// subtasks are indented by two spaces or a tab per level below their parent
+pub const INDENT: &str = "  ";
+
+// nesting depth of an indented task line and the line without the indentation,
+// lines that are not tasks once unindented keep their leading whitespace
+pub fn split_indentation(line: &str) -> (usize, &str) {
+    let text = line.trim_start_matches([' ', '\t']);
+    if get_type_from_string(text) == TaskType::NotDefined {
+        return (0, line);
+    }
+    let indentation = &line[..line.len() - text.len()];
+    let depth = indentation.matches('\t').count() + indentation.matches(' ').count() / 2;
+    (depth, text)
+}
+
+// an indented line that is not a task continues the note of the task above it
+pub fn is_note_line(line: &str) -> bool {
+    let text = line.trim_start();
+    text.len() < line.len()
+        && !text.is_empty()
+        && !text.starts_with('#')
+        && get_type_from_string(text) == TaskType::NotDefined
+}
+
+// a note that would read back as a comment, a subtask or a tsv task is saved behind a
+// backslash, which loading takes off again
+const NOTE_ESCAPE: char = '\\';
+
+pub fn escape_note(note: &str) -> String {
+    let text = note.trim_start();
+    let is_tsv_task = text
+        .split_once('\t')
+        .is_some_and(|(keyword, _)| TaskType::from_keyword(keyword).is_some());
+    if text.starts_with(['#', NOTE_ESCAPE])
+        || get_type_from_string(text) != TaskType::NotDefined
+        || is_tsv_task
+    {
+        format!("{}{}", NOTE_ESCAPE, note)
+    } else {
+        note.to_string()
+    }
+}
+
+pub fn unescape_note(line: &str) -> &str {
+    line.strip_prefix(NOTE_ESCAPE).unwrap_or(line)
+}
+
+// blank lines and lines starting with # are kept in the file as they are but are not tasks
+pub fn is_comment_line(text: &str) -> bool {
+    let text = text.trim();
+    text.is_empty() || text.starts_with('#')
+}
+
+// a line like "--- Groceries ---" starts a section of the list
+pub const HEADER_MARKER: &str = "---";
+
+pub fn is_header_line(text: &str) -> bool {
+    text.trim_start().starts_with(HEADER_MARKER)
+}
+
+// what a line of the file is, only tasks are sorted and can be selected
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum LineKind {
+    Task,
+    // blank or starting with #, kept in the file but not shown
+    Comment,
+    // shown above the tasks that follow it in the file
+    Header,
+}
+
+pub fn get_line_kind(text: &str) -> LineKind {
+    if is_comment_line(text) {
+        LineKind::Comment
+    } else if is_header_line(text) {
+        LineKind::Header
+    } else {
+        LineKind::Task
+    }
+}
+
+// the text of a task without its status marker
+pub fn strip_type_prefix(text: &str) -> &str {
+    if get_type_from_string(text) == TaskType::NotDefined {
+        text
+    } else {
+        text[3..].trim_start()
+    }
+}
+
+// the priority is stored as a `~` (low) or `!!` (high) word right after the status, medium is
+// written unmarked but a `!` word still reads as medium, a description that merely starts with
+// ! or ~ is not a marker
+pub fn get_priority_from_string(text: &str) -> Priority {
+    if get_type_from_string(text) == TaskType::NotDefined {
+        return Priority::default();
+    }
+    match strip_type_prefix(text).split_whitespace().next() {
+        Some("!!") => Priority::High,
+        Some("!") => Priority::Medium,
+        Some("~") => Priority::Low,
+        _ => Priority::default(),
+    }
+}
+
+pub fn priority_to_string(priority: Priority) -> String {
+    match priority {
+        Priority::Low => "~".to_string(),
+        Priority::Medium => "".to_string(),
+        Priority::High => "!!".to_string(),
+    }
+}
+
+// the text of a task without its status and priority markers
+pub fn get_description(text: &str) -> &str {
+    if get_type_from_string(text) == TaskType::NotDefined {
+        text
+    } else {
+        let text = strip_type_prefix(text);
+        match text.split_whitespace().next() {
+            Some(marker @ ("~" | "!" | "!!")) => text[marker.len()..].trim_start(),
+            _ => text,
+        }
+    }
+}
+
+// build the stored line of a task from its parts
+pub fn compose_task_text(task_type: TaskType, priority: Priority, description: &str) -> String {
+    let marker = priority_to_string(priority);
+    if marker.is_empty() {
+        format!("{} {}", type_to_string(task_type), description)
+    } else {
+        format!("{} {} {}", type_to_string(task_type), marker, description)
+    }
+}
+
+// due dates are stored in the line as a due:YYYY-MM-DD word
+pub const DUE_DATE_MARKER: &str = "due:";
+
+// creation and completion dates are stored the same way as created:/completed: words
+pub const CREATED_MARKER: &str = "created:";
+pub const COMPLETED_MARKER: &str = "completed:";
+pub const ARCHIVED_MARKER: &str = "archived:";
+
+pub fn get_date_from_string(text: &str, marker: &str) -> Option<Date> {
+    text.split_whitespace()
+        .find_map(|word| word.strip_prefix(marker))
+        .and_then(Date::parse)
+}
+
+pub fn get_due_date_from_string(text: &str) -> Option<Date> {
+    get_date_from_string(text, DUE_DATE_MARKER)
+}
+
+// replace the date stored under the marker, None removes it
+pub fn set_date_in_string(text: &str, marker: &str, date: Option<Date>) -> String {
+    set_word_in_string(text, marker, date.map(|date| date.to_string()))
+}
+
+fn set_word_in_string(text: &str, marker: &str, value: Option<String>) -> String {
+    let text = text
+        .split(' ')
+        .filter(|word| !word.starts_with(marker))
+        .collect::<Vec<&str>>()
+        .join(" ");
+    match value {
+        Some(value) => format!("{} {}{}", text.trim_end(), marker, value),
+        None => text,
+    }
+}
+
+// a task can be flagged with a color of its own, stored as a color:NAME word
+pub const COLOR_MARKER: &str = "color:";
+
+pub fn get_color_override_from_string(text: &str) -> Option<ConsoleForegroundColors> {
+    text.split_whitespace()
+        .find_map(|word| word.strip_prefix(COLOR_MARKER))
+        .and_then(get_color_from_string)
+}
+
+pub fn set_color_in_string(text: &str, color: Option<ConsoleForegroundColors>) -> String {
+    set_word_in_string(
+        text,
+        COLOR_MARKER,
+        color.map(|color| color_to_string(color).to_string()),
+    )
+}
+
+pub const RECURRING_MARKER: &str = "@daily";
+
+pub fn is_recurring(text: &str) -> bool {
+    text.split_whitespace().any(|word| word == RECURRING_MARKER)
+}
+
+// the line as drawn in the list, the creation and completion dates are only on the status bar
+// and the color override is seen in the color of the line
+pub fn get_display_text(text: &str) -> String {
+    text.split(' ')
+        .filter(|word| {
+            !word.starts_with(CREATED_MARKER)
+                && !word.starts_with(COMPLETED_MARKER)
+                && !word.starts_with(COLOR_MARKER)
+        })
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .trim_end()
+        .to_string()
+}
+
+pub fn type_to_string(task_type: TaskType) -> String {
+    match task_type {
+        TaskType::Todo => "[ ]".to_string(),
+        TaskType::Doing => "[+]".to_string(),
+        TaskType::Done => "[X]".to_string(),
+        TaskType::Rejected => "[-]".to_string(),
+        TaskType::NotDefined => "[ ]".to_string(),
+    }
+}
+
+#[derive(Clone)]
+pub struct Task {
+    pub kind: LineKind,
+    pub task_type: TaskType,
+    pub priority: Priority,
+    pub due: Option<Date>,
+    pub created_at: Option<Date>,
+    pub completed_at: Option<Date>,
+    pub tags: Vec<String>,
+    // done tasks marked with @daily are reset to todo on the next day
+    pub recurring: bool,
+    // drawn in this color instead of the one of its status
+    pub color_override: Option<ConsoleForegroundColors>,
+    // nesting level, 0 for top level tasks
+    pub depth: usize,
+    // longer description kept on the indented lines below the task, lines separated by \n
+    pub notes: String,
+    pub text: String,
+}
+
+impl Task {
+    pub fn new(line: &str, task_type: TaskType) -> Self {
+        let (depth, text) = split_indentation(line);
+        // the status of an indented line can only be read once the indentation is gone
+        let task_type = if depth > 0 {
+            get_type_from_string(text)
+        } else {
+            task_type
+        };
+        Self {
+            kind: get_line_kind(text),
+            task_type,
+            priority: get_priority_from_string(text),
+            due: get_due_date_from_string(text),
+            created_at: get_date_from_string(text, CREATED_MARKER),
+            completed_at: get_date_from_string(text, COMPLETED_MARKER),
+            tags: get_tags_from_string(text),
+            recurring: is_recurring(text),
+            color_override: get_color_override_from_string(text),
+            depth,
+            notes: String::new(),
+            text: text.to_string(),
+        }
+    }
+
+    // the line as stored in the file, with its indentation
+    pub fn line(&self) -> String {
+        format!("{}{}", INDENT.repeat(self.depth), self.text)
+    }
+
+    // the notes as stored below the line, each on its own line one level deeper
+    pub fn note_lines(&self) -> String {
+        let indentation = INDENT.repeat(self.depth + 1);
+        self.notes
+            .lines()
+            .map(|note| format!("\n{}{}", indentation, escape_note(note)))
+            .collect()
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|own| own.eq_ignore_ascii_case(tag))
+    }
+
+    // comments and headers are kept in place in the file but are not tasks
+    pub fn is_comment(&self) -> bool {
+        self.kind != LineKind::Task
+    }
+
+    pub fn is_header(&self) -> bool {
+        self.kind == LineKind::Header
+    }
+
+    // past its due date and still open, done and rejected tasks are never overdue
+    pub fn is_overdue(&self, today: Date) -> bool {
+        self.due.is_some_and(|due| due < today)
+            && !matches!(self.task_type, TaskType::Done | TaskType::Rejected)
+    }
+
+    // replace the whole line and re-read the markers stored in it, returns the previous line
+    pub fn set_text(&mut self, text: String) -> String {
+        self.kind = get_line_kind(&text);
+        self.task_type = get_type_from_string(&text);
+        self.priority = get_priority_from_string(&text);
+        self.due = get_due_date_from_string(&text);
+        self.created_at = get_date_from_string(&text, CREATED_MARKER);
+        self.completed_at = get_date_from_string(&text, COMPLETED_MARKER);
+        self.tags = get_tags_from_string(&text);
+        self.recurring = is_recurring(&text);
+        self.color_override = get_color_override_from_string(&text);
+        std::mem::replace(&mut self.text, text)
+    }
+
+    // the text without the status marker, the whole text when there is none
+    pub fn display_text(&self) -> &str {
+        strip_type_prefix(&self.text)
+    }
+
+    // replace the text after the status marker, keeps the marker, returns the previous line
+    pub fn set_display_text(&mut self, text: &str) -> String {
+        let text = match get_type_from_string(&self.text) {
+            TaskType::NotDefined => text.to_string(),
+            task_type => format!("{} {}", type_to_string(task_type), text.trim_start()),
+        };
+        self.set_text(text)
+    }
+
+    // completing a task stamps today's date, any other status drops it
+    pub fn set_type(&mut self, task_type: TaskType) {
+        let marker = type_to_string(task_type);
+        // a hand edited line can be missing the marker or be shorter than one, the fields are
+        // parsed again from the new line so they agree with its marker
+        let text = if get_type_from_string(&self.text) == TaskType::NotDefined {
+            format!("{} {}", marker, self.text.trim_start())
+                .trim_end()
+                .to_string()
+        } else {
+            format!("{}{}", marker, &self.text[3..])
+        };
+        self.set_text(text);
+        let completed_at = if self.task_type == TaskType::Done {
+            Some(Date::today())
+        } else {
+            None
+        };
+        if completed_at != self.completed_at {
+            self.set_text(set_date_in_string(
+                &self.text,
+                COMPLETED_MARKER,
+                completed_at,
+            ));
+        }
+    }
+}
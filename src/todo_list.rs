@@ -0,0 +1,1139 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::color::ConsoleForegroundColors;
+use crate::date::{timestamp, Date};
+use crate::json::{is_json_path, tasks_from_json, tasks_to_json};
+use crate::task::{
+    compose_task_text, get_date_from_string, get_description, get_display_text, get_line_kind,
+    get_type_from_string, is_note_line, set_color_in_string, set_date_in_string, unescape_note,
+    LineKind, Priority, Task, TaskType, ARCHIVED_MARKER, COMPLETED_MARKER, CREATED_MARKER,
+    DUE_DATE_MARKER,
+};
+use crate::tsv::{tasks_from_tsv, tasks_to_tsv};
+
+// maximum number of changes kept for undo
+const HISTORY_LIMIT: usize = 100;
+
+// a single mutation of the list, holding what is needed to revert it
+enum Change {
+    Added(usize),
+    Deleted(usize, Task),
+    TextChanged(usize, String),
+    NotesChanged(usize, String),
+    // the task at the index had the depth before it was dedented
+    DepthChanged(usize, usize),
+    // the task now at the first index was moved there from the second
+    Moved(usize, usize),
+    // several changes made at once, in the order they were applied
+    Batch(Vec<Change>),
+}
+
+// number of tasks in each status
+#[derive(Default)]
+pub struct TaskCounts {
+    pub todo: usize,
+    pub doing: usize,
+    pub done: usize,
+    pub rejected: usize,
+}
+
+// width of the progress bar between its brackets
+pub const PROGRESS_BAR_WIDTH: usize = 10;
+
+impl TaskCounts {
+    // percentage of done tasks, rejected tasks count neither as done nor as left to do
+    pub fn percent_done(&self) -> usize {
+        let total = self.todo + self.doing + self.done;
+        (self.done * 100).checked_div(total).unwrap_or(0)
+    }
+
+    // e.g. [#####-----] 50%
+    pub fn progress_bar(&self) -> String {
+        let percent = self.percent_done();
+        let filled = percent * PROGRESS_BAR_WIDTH / 100;
+        format!(
+            "[{}{}] {}%",
+            "#".repeat(filled),
+            "-".repeat(PROGRESS_BAR_WIDTH - filled),
+            percent
+        )
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SortMode {
+    Status,
+    // todo and doing tasks above done and rejected ones, each in file order
+    ActiveFirst,
+    Alphabetical,
+    Insertion,
+}
+
+impl SortMode {
+    pub fn from_name(name: &str) -> Option<SortMode> {
+        match name.trim().to_lowercase().as_str() {
+            "status" => Some(SortMode::Status),
+            "open" => Some(SortMode::ActiveFirst),
+            "text" => Some(SortMode::Alphabetical),
+            "insertion" => Some(SortMode::Insertion),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> SortMode {
+        match self {
+            SortMode::Status => SortMode::ActiveFirst,
+            SortMode::ActiveFirst => SortMode::Alphabetical,
+            SortMode::Alphabetical => SortMode::Insertion,
+            SortMode::Insertion => SortMode::Status,
+        }
+    }
+}
+
+// how the tasks are stored on disk
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FileFormat {
+    Text,
+    Json,
+    Tsv,
+}
+
+impl FileFormat {
+    pub fn from_name(name: &str) -> Option<FileFormat> {
+        match name.trim().to_lowercase().as_str() {
+            "text" | "txt" => Some(FileFormat::Text),
+            "json" => Some(FileFormat::Json),
+            "tsv" => Some(FileFormat::Tsv),
+            _ => None,
+        }
+    }
+}
+
+pub struct TodoList {
+    pub tasks: Vec<Task>,
+    pub is_editing: bool,
+    pub sort_mode: SortMode,
+    // only tasks containing this text (case-insensitive) are shown
+    pub filter: String,
+    // only tasks with this #tag are shown when set
+    pub tag_filter: String,
+    // tasks with these statuses are not shown
+    pub hidden_types: Vec<TaskType>,
+    // the hidden statuses from before focus mode, which shows only the doing tasks
+    unfocused_types: Option<Vec<TaskType>>,
+    // only the doing tasks and the ones due today are shown, whatever their status
+    showing_today: bool,
+    // set by every change, cleared when the list is loaded or saved
+    pub is_dirty: bool,
+    // every status change is appended to this file when set
+    pub log_path: Option<PathBuf>,
+    // files ending in .json are read and written as JSON unless another format is chosen
+    pub format: FileFormat,
+    history: Vec<Change>,
+    redo_history: Vec<Change>,
+}
+
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Default for TodoList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TodoList {
+    pub fn new() -> Self {
+        Self {
+            tasks: Vec::new(),
+            is_editing: false,
+            sort_mode: SortMode::Status,
+            filter: String::new(),
+            tag_filter: String::new(),
+            hidden_types: Vec::new(),
+            unfocused_types: None,
+            showing_today: false,
+            history: Vec::new(),
+            redo_history: Vec::new(),
+            is_dirty: false,
+            log_path: None,
+            format: FileFormat::Text,
+        }
+    }
+
+    fn format_for(&self, file_path: &str) -> FileFormat {
+        if self.format == FileFormat::Text && is_json_path(file_path) {
+            FileFormat::Json
+        } else {
+            self.format
+        }
+    }
+
+    // read the tasks from the file, creating it when it does not exist yet
+    pub fn load(&mut self, file_path: &str) -> io::Result<()> {
+        let file = match File::open(file_path) {
+            Ok(file) => file,
+            Err(_) => {
+                File::create(file_path)?;
+                File::open(file_path)?
+            }
+        };
+        let mut reader = BufReader::new(file);
+        self.tasks = Vec::new();
+        let format = self.format_for(file_path);
+        if format == FileFormat::Json {
+            let mut content = Vec::new();
+            reader.read_to_end(&mut content)?;
+            self.tasks = tasks_from_json(&String::from_utf8_lossy(&content)).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "the file is not a valid JSON task list",
+                )
+            })?;
+        } else if format == FileFormat::Tsv {
+            let mut content = Vec::new();
+            reader.read_to_end(&mut content)?;
+            self.tasks = tasks_from_tsv(&String::from_utf8_lossy(&content));
+        } else {
+            let mut content = Vec::new();
+            reader.read_to_end(&mut content)?;
+            // invalid UTF-8 is replaced rather than dropped so no line gets lost on the next save
+            self.tasks = tasks_from_text(&String::from_utf8_lossy(&content));
+        }
+        let reset = self.reset_recurring(Date::today());
+        self.history.clear();
+        self.redo_history.clear();
+        self.is_dirty = reset > 0;
+        Ok(())
+    }
+
+    // recurring tasks completed before today are open again, returns how many were reset
+    pub fn reset_recurring(&mut self, today: Date) -> usize {
+        let mut count = 0;
+        for task in &mut self.tasks {
+            let stale = task
+                .completed_at
+                .is_some_and(|completed_at| completed_at < today);
+            if task.recurring && task.task_type == TaskType::Done && stale {
+                task.set_type(TaskType::Todo);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    // new tasks are stamped with today's date unless the text already has one
+    pub fn add(&mut self, text: &str, task_type: TaskType) {
+        self.insert(self.tasks.len(), text, task_type);
+    }
+
+    // insert a new task so it ends up at the index, the tasks from there on move down
+    pub fn insert(&mut self, index: usize, text: &str, task_type: TaskType) {
+        let text = if get_line_kind(text) != LineKind::Task
+            || get_date_from_string(text, CREATED_MARKER).is_some()
+        {
+            text.to_string()
+        } else {
+            set_date_in_string(text, CREATED_MARKER, Some(Date::today()))
+        };
+        let new_task = Task::new(&text, task_type);
+        self.tasks.insert(index, new_task);
+        self.record(Change::Added(index));
+    }
+
+    // insert a todo copy of the task below it, returns the index of the copy
+    pub fn duplicate_task(&mut self, index: usize) -> Option<usize> {
+        let mut task = self.tasks.get(index)?.clone();
+        if task.task_type != TaskType::NotDefined {
+            task.set_type(TaskType::Todo);
+        }
+        task.set_text(set_date_in_string(
+            &task.text,
+            CREATED_MARKER,
+            Some(Date::today()),
+        ));
+        // below the subtasks of the original, so they stay with it
+        let position = index + self.children(index).len() + 1;
+        self.tasks.insert(position, task);
+        self.record(Change::Added(position));
+        Some(position)
+    }
+
+    // split the description at the character offset, the second half becomes a task with the
+    // same status below the first, returns its index or None when a half would be empty.
+    // The dates stay with the first half, the second gets its own stamps of today
+    pub fn split_task(&mut self, index: usize, at: usize) -> Option<usize> {
+        let task = self.tasks.get(index).filter(|task| !task.is_comment())?;
+        let description = get_description(&task.text);
+        let at = description
+            .char_indices()
+            .nth(at)
+            .map_or(description.len(), |(offset, _)| offset);
+        let is_stamp = |word: &&str| {
+            [CREATED_MARKER, COMPLETED_MARKER, DUE_DATE_MARKER]
+                .iter()
+                .any(|marker| word.starts_with(marker))
+        };
+        let without_stamps = |half: &str| {
+            half.split(' ')
+                .filter(|word| !is_stamp(word))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        // a date is never cut in two, the split moves to its start
+        let word_start = description[..at].rfind(' ').map_or(0, |space| space + 1);
+        let at = if description[word_start..]
+            .split(' ')
+            .next()
+            .is_some_and(|word| is_stamp(&word))
+        {
+            word_start
+        } else {
+            at
+        };
+        let (first, second) = description.split_at(at);
+        let (first, second) = (without_stamps(first), without_stamps(second));
+        let (mut first, mut second) = (first.trim().to_string(), second.trim().to_string());
+        if first.is_empty() || second.is_empty() {
+            return None;
+        }
+        for stamp in description.split(' ').filter(is_stamp) {
+            first = format!("{} {}", first, stamp);
+        }
+        let today = Some(Date::today());
+        if task.created_at.is_some() {
+            second = set_date_in_string(&second, CREATED_MARKER, today);
+        }
+        if task.completed_at.is_some() {
+            second = set_date_in_string(&second, COMPLETED_MARKER, today);
+        }
+        let compose = |description: &str| {
+            if task.task_type == TaskType::NotDefined {
+                description.to_string()
+            } else {
+                compose_task_text(task.task_type, task.priority, description)
+            }
+        };
+        let (first, second) = (compose(&first), compose(&second));
+        let mut new_task = task.clone();
+        new_task.set_text(second);
+        new_task.notes.clear();
+        let previous = self.tasks[index].set_text(first);
+        // below the subtasks, which stay with the first half
+        let position = index + self.children(index).len() + 1;
+        self.tasks.insert(position, new_task);
+        self.record(Change::Batch(vec![
+            Change::TextChanged(index, previous),
+            Change::Added(position),
+        ]));
+        Some(position)
+    }
+
+    // append the description and notes of the task on the next row to the task and remove
+    // it, the status of the task stays; returns the index of the task afterwards, None when
+    // there is no task right below or it is behind a header or at another depth
+    pub fn merge_with_next(&mut self, index: usize) -> Option<usize> {
+        if self.tasks.get(index).is_none_or(|task| task.is_comment()) {
+            return None;
+        }
+        let view_order = self.view_order();
+        let next = view_order
+            .iter()
+            .position(|&i| i == index)
+            .and_then(|position| view_order.get(position + 1))
+            .copied()
+            .filter(|&next| {
+                !self.tasks[next].is_header() && self.tasks[next].depth == self.tasks[index].depth
+            })?;
+        let next_task = self.tasks[next].clone();
+        let task = &mut self.tasks[index];
+        // the dates of the task are the ones that count, the due date of the next one only
+        // when the task has none
+        let mut appended = get_description(&next_task.text).to_string();
+        for marker in [CREATED_MARKER, COMPLETED_MARKER] {
+            appended = set_date_in_string(&appended, marker, None);
+        }
+        if task.due.is_some() {
+            appended = set_date_in_string(&appended, DUE_DATE_MARKER, None);
+        }
+        let description = format!("{} {}", get_description(&task.text), appended.trim());
+        let text = if task.task_type == TaskType::NotDefined {
+            description
+        } else {
+            compose_task_text(task.task_type, task.priority, &description)
+        };
+        let previous = task.set_text(text);
+        let previous_notes = task.notes.clone();
+        if !next_task.notes.is_empty() {
+            if !task.notes.is_empty() {
+                task.notes.push('\n');
+            }
+            task.notes.push_str(&next_task.notes);
+        }
+        // the first change is the one undo puts the cursor on
+        let mut changes = vec![
+            Change::TextChanged(index, previous),
+            Change::NotesChanged(index, previous_notes),
+        ];
+        changes.extend(self.dedent_children(next));
+        changes.push(Change::Deleted(next, self.tasks.remove(next)));
+        self.record_all(changes);
+        Some(if next < index { index - 1 } else { index })
+    }
+
+    // the subtasks of the task move up a level so they are not left without a parent
+    pub fn delete_task(&mut self, index: usize) {
+        if index < self.tasks.len() {
+            let mut changes = self.dedent_children(index);
+            let task = self.tasks.remove(index);
+            changes.push(Change::Deleted(index, task));
+            self.record_all(changes);
+        }
+    }
+
+    fn dedent_children(&mut self, index: usize) -> Vec<Change> {
+        let children = self.children(index);
+        children
+            .into_iter()
+            .map(|child| {
+                self.tasks[child].depth -= 1;
+                Change::DepthChanged(child, self.tasks[child].depth + 1)
+            })
+            .collect()
+    }
+
+    // move every done and rejected task to the end of the archive file, returns how many
+    // were moved, the move cannot be undone since the archive keeps them anyway
+    pub fn archive_completed(&mut self, archive_path: &Path) -> io::Result<usize> {
+        let today = Date::today();
+        let mut archived = String::new();
+        for task in &self.tasks {
+            if matches!(task.task_type, TaskType::Done | TaskType::Rejected) {
+                let line = set_date_in_string(&task.line(), ARCHIVED_MARKER, Some(today));
+                archived.push_str(&line);
+                archived.push_str(&task.note_lines());
+                archived.push('\n');
+            }
+        }
+        if archived.is_empty() {
+            return Ok(0);
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(archive_path)?
+            .write_all(archived.as_bytes())?;
+        let mut count = 0;
+        for index in (0..self.tasks.len()).rev() {
+            if matches!(
+                self.tasks[index].task_type,
+                TaskType::Done | TaskType::Rejected
+            ) {
+                // open subtasks stay in the list, a level up
+                self.dedent_children(index);
+                self.tasks.remove(index);
+                count += 1;
+            }
+        }
+        self.history.clear();
+        self.redo_history.clear();
+        self.is_dirty = true;
+        Ok(count)
+    }
+
+    // move the task at the index among the archived ones back to the end of the list with
+    // the status it was archived with, the line is removed from the archive file. Returns
+    // the index of the restored task, None when the archive has no task at the index
+    pub fn unarchive(&mut self, archive_path: &Path, index: usize) -> io::Result<Option<usize>> {
+        let mut archived = tasks_from_text(&String::from_utf8_lossy(&fs::read(archive_path)?));
+        let Some(position) = archive_positions(&archived).get(index).copied() else {
+            return Ok(None);
+        };
+        let task = archived.remove(position);
+        let content: String = archived
+            .iter()
+            .map(|task| format!("{}{}\n", task.line(), task.note_lines()))
+            .collect();
+        fs::write(archive_path, content)?;
+        let text = set_date_in_string(&task.text, ARCHIVED_MARKER, None);
+        let mut restored = Task::new(&text, get_type_from_string(&text));
+        restored.notes = task.notes;
+        self.tasks.push(restored);
+        self.history.clear();
+        self.redo_history.clear();
+        self.is_dirty = true;
+        Ok(Some(self.tasks.len() - 1))
+    }
+
+    // remove every done and rejected task, returns how many were removed; open subtasks
+    // of a removed task move up a level
+    pub fn clear_completed(&mut self) -> usize {
+        let mut changes = Vec::new();
+        let mut count = 0;
+        for index in (0..self.tasks.len()).rev() {
+            if matches!(
+                self.tasks[index].task_type,
+                TaskType::Done | TaskType::Rejected
+            ) {
+                changes.extend(self.dedent_children(index));
+                let task = self.tasks.remove(index);
+                changes.push(Change::Deleted(index, task));
+                count += 1;
+            }
+        }
+        if count > 0 {
+            self.record(Change::Batch(changes));
+        }
+        count
+    }
+
+    // tasks repeating an earlier task under the same parent, ignoring the status, priority and
+    // dates; of every group the one with the most advanced status is kept, in file order
+    pub fn duplicates(&self) -> Vec<usize> {
+        let mut kept: Vec<(Option<usize>, String, usize)> = Vec::new();
+        let mut duplicates = Vec::new();
+        for index in 0..self.tasks.len() {
+            let task = &self.tasks[index];
+            // removing a task with subtasks would hand them to another parent
+            if task.task_type == TaskType::NotDefined || !self.children(index).is_empty() {
+                continue;
+            }
+            let parent = self.parent(index);
+            let description = get_description(&get_display_text(&task.text)).to_string();
+            match kept
+                .iter_mut()
+                .find(|(p, d, _)| *p == parent && *d == description)
+            {
+                Some((_, _, kept_index)) if task.task_type > self.tasks[*kept_index].task_type => {
+                    duplicates.push(*kept_index);
+                    *kept_index = index;
+                }
+                Some(_) => duplicates.push(index),
+                None => kept.push((parent, description, index)),
+            }
+        }
+        duplicates.sort_unstable();
+        duplicates
+    }
+
+    // remove the duplicates as a single undo step, returns how many were removed
+    pub fn dedupe(&mut self) -> usize {
+        let mut changes = Vec::new();
+        for index in self.duplicates().into_iter().rev() {
+            let task = self.tasks.remove(index);
+            changes.push(Change::Deleted(index, task));
+        }
+        let count = changes.len();
+        if count > 0 {
+            self.record(Change::Batch(changes));
+        }
+        count
+    }
+
+    // give every line without a status marker the todo status, returns how many were fixed
+    pub fn fix_undefined(&mut self) -> usize {
+        let mut changes = Vec::new();
+        for (index, task) in self.tasks.iter_mut().enumerate() {
+            if task.task_type == TaskType::NotDefined && !task.is_comment() {
+                let text = compose_task_text(TaskType::Todo, Priority::default(), task.text.trim());
+                changes.push(Change::TextChanged(index, task.set_text(text)));
+            }
+        }
+        let count = changes.len();
+        if count > 0 {
+            self.record(Change::Batch(changes));
+        }
+        count
+    }
+
+    pub fn change_type(&mut self, index: usize) {
+        if let Some(task) = self.tasks.get(index) {
+            self.set_type(index, task.task_type.next());
+        }
+    }
+
+    // completing a task completes its subtasks, and completing the last open subtask
+    // completes the parent
+    pub fn set_type(&mut self, index: usize, task_type: TaskType) {
+        if index >= self.tasks.len() {
+            return;
+        }
+        let mut changes = vec![self.retype(index, task_type)];
+        if task_type == TaskType::Done {
+            for child in self.children(index) {
+                if self.tasks[child].task_type != TaskType::Done {
+                    changes.push(self.retype(child, TaskType::Done));
+                }
+            }
+        }
+        let mut child = index;
+        while let Some(parent) = self.parent(child) {
+            let all_done = self
+                .children(parent)
+                .iter()
+                .all(|&i| self.tasks[i].task_type == TaskType::Done);
+            if !all_done || self.tasks[parent].task_type == TaskType::Done {
+                break;
+            }
+            changes.push(self.retype(parent, TaskType::Done));
+            child = parent;
+        }
+        self.record_all(changes);
+    }
+
+    // set the status of every task in the view, so a search limits it to the matching ones,
+    // returns how many tasks changed
+    pub fn mark_all(&mut self, task_type: TaskType) -> usize {
+        self.set_types(&self.view_order(), task_type)
+    }
+
+    // give the tasks the status as a single undo step, returns how many changed
+    pub fn set_types(&mut self, indices: &[usize], task_type: TaskType) -> usize {
+        let mut changes = Vec::new();
+        for &index in indices {
+            let current = self.tasks[index].task_type;
+            if current != task_type && current != TaskType::NotDefined {
+                changes.push(self.retype(index, task_type));
+            }
+        }
+        let count = changes.len();
+        if count > 0 {
+            self.record(Change::Batch(changes));
+        }
+        count
+    }
+
+    // the whole line is kept for undo since the status can also change the completion date
+    fn retype(&mut self, index: usize, task_type: TaskType) -> Change {
+        let previous = self.tasks[index].text.clone();
+        let previous_type = self.tasks[index].task_type;
+        self.tasks[index].set_type(task_type);
+        if previous_type != task_type {
+            self.log_status_change(index, previous_type);
+        }
+        Change::TextChanged(index, previous)
+    }
+
+    // one tab separated line per change: time, old status, new status and the description
+    fn log_status_change(&self, index: usize, previous_type: TaskType) {
+        let Some(log_path) = &self.log_path else {
+            return;
+        };
+        let task = &self.tasks[index];
+        let line = format!(
+            "{}\t{}\t{}\t{}\n",
+            timestamp(),
+            previous_type,
+            task.task_type,
+            get_description(&get_display_text(&task.text))
+        );
+        // a missing log line is not worth interrupting the edit over
+        let _ = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_path)
+            .and_then(|mut file| file.write_all(line.as_bytes()));
+    }
+
+    // the tasks nested under the task, i.e. the ones right after it that are indented deeper
+    pub fn children(&self, index: usize) -> Vec<usize> {
+        let depth = self.tasks[index].depth;
+        (index + 1..self.tasks.len())
+            .take_while(|&i| self.tasks[i].depth > depth)
+            .collect()
+    }
+
+    pub fn parent(&self, index: usize) -> Option<usize> {
+        let depth = self.tasks[index].depth;
+        (0..index)
+            .rev()
+            .find(|&i| self.tasks[i].depth < depth)
+            .filter(|&i| !self.tasks[i].is_comment())
+    }
+
+    // replace the description of a task while keeping its status prefix
+    pub fn edit_task(&mut self, index: usize, description: &str) {
+        if let Some(task) = self.tasks.get_mut(index) {
+            let text = if task.task_type == TaskType::NotDefined {
+                description.to_string()
+            } else {
+                compose_task_text(task.task_type, task.priority, description)
+            };
+            let previous = task.set_text(text);
+            self.record(Change::TextChanged(index, previous));
+        }
+    }
+
+    // swap a task and its subtasks with the neighbouring task at the same level and its
+    // subtasks, returns the new index of the task
+    pub fn move_task(&mut self, index: usize, direction: Direction) -> Option<usize> {
+        let len = self.block_len(index);
+        // comment lines in between stay where they are, the blocks jump over them; the
+        // moved block goes first so undo returns the index it came from
+        let (changes, new_index) = match direction {
+            Direction::Up => {
+                let other = self.sibling(index, (0..index).rev())?;
+                let other_len = self.block_len(other);
+                let gap = index - other - other_len;
+                let mut changes = self.shift(index, len, other);
+                changes.extend(self.shift(other + len + other_len, gap, other + len));
+                (changes, other)
+            }
+            Direction::Down => {
+                let other = self.sibling(index, index + len..self.tasks.len())?;
+                let other_len = self.block_len(other);
+                let gap = other - index - len;
+                let target = other + other_len - len;
+                let mut changes = self.shift(index, len, target);
+                changes.extend(self.shift(index + gap, other_len, index));
+                (changes, target)
+            }
+            _ => return None,
+        };
+        self.record_all(changes);
+        Some(new_index)
+    }
+
+    // move a task and its subtasks before the first task at its level, i.e. to the top of
+    // the list or of its parent; returns its new index
+    pub fn move_to_top(&mut self, index: usize) -> Option<usize> {
+        let start = self.parent(index).map_or(0, |parent| parent + 1);
+        let target = self.sibling(index, start..index)?;
+        let changes = self.shift(index, self.block_len(index), target);
+        self.record_all(changes);
+        Some(target)
+    }
+
+    // move a task and its subtasks after the last task at its level, returns its new index
+    pub fn move_to_bottom(&mut self, index: usize) -> Option<usize> {
+        let end = self
+            .parent(index)
+            .map_or(self.tasks.len(), |parent| parent + self.block_len(parent));
+        let len = self.block_len(index);
+        let last = self.sibling(index, (index + len..end).rev())?;
+        let target = last + self.block_len(last) - len;
+        let changes = self.shift(index, len, target);
+        self.record_all(changes);
+        Some(target)
+    }
+
+    // the task and its subtasks
+    fn block_len(&self, index: usize) -> usize {
+        self.children(index).len() + 1
+    }
+
+    // the first task in the range at the level of the task, None when there is none or the
+    // range reaches the parent of the task first
+    fn sibling(&self, index: usize, mut range: impl Iterator<Item = usize>) -> Option<usize> {
+        let depth = self.tasks[index].depth;
+        range
+            .find(|&i| !self.tasks[i].is_comment() && self.tasks[i].depth <= depth)
+            .filter(|&i| self.tasks[i].depth == depth)
+    }
+
+    // move the lines starting at the index so the first ends up at the target, the lines in
+    // between shift and keep their order
+    fn shift(&mut self, index: usize, len: usize, target: usize) -> Vec<Change> {
+        (0..len)
+            .map(|offset| {
+                let (from, to) = if target < index {
+                    (index + offset, target + offset)
+                } else {
+                    (index, target + len - 1)
+                };
+                let task = self.tasks.remove(from);
+                self.tasks.insert(to, task);
+                Change::Moved(to, from)
+            })
+            .collect()
+    }
+
+    pub fn set_priority(&mut self, index: usize, priority: Priority) {
+        if let Some(task) = self.tasks.get_mut(index) {
+            if task.task_type == TaskType::NotDefined || task.priority == priority {
+                return;
+            }
+            let text = compose_task_text(task.task_type, priority, get_description(&task.text));
+            let previous = task.set_text(text);
+            self.record(Change::TextChanged(index, previous));
+        }
+    }
+
+    pub fn set_notes(&mut self, index: usize, notes: &str) {
+        if let Some(task) = self.tasks.get_mut(index) {
+            if task.notes == notes {
+                return;
+            }
+            let previous = std::mem::replace(&mut task.notes, notes.to_string());
+            self.record(Change::NotesChanged(index, previous));
+        }
+    }
+
+    pub fn set_due_date(&mut self, index: usize, due: Option<Date>) {
+        if let Some(task) = self.tasks.get_mut(index) {
+            if task.due == due {
+                return;
+            }
+            let text = set_date_in_string(&task.text, DUE_DATE_MARKER, due);
+            let previous = task.set_text(text);
+            self.record(Change::TextChanged(index, previous));
+        }
+    }
+
+    pub fn set_color_override(&mut self, index: usize, color: Option<ConsoleForegroundColors>) {
+        if let Some(task) = self.tasks.get_mut(index) {
+            if task.color_override == color {
+                return;
+            }
+            let text = set_color_in_string(&task.text, color);
+            let previous = task.set_text(text);
+            self.record(Change::TextChanged(index, previous));
+        }
+    }
+
+    // a single change is kept as is so undo reports its index
+    fn record_all(&mut self, mut changes: Vec<Change>) {
+        let change = if changes.len() == 1 {
+            changes.remove(0)
+        } else {
+            Change::Batch(changes)
+        };
+        self.record(change);
+    }
+
+    fn record(&mut self, change: Change) {
+        self.is_dirty = true;
+        self.redo_history.clear();
+        self.push_history(change);
+    }
+
+    fn push_history(&mut self, change: Change) {
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.remove(0);
+        }
+        self.history.push(change);
+    }
+
+    // revert the last change, returns the index of the affected task
+    pub fn undo(&mut self) -> Option<usize> {
+        let change = self.history.pop()?;
+        let (inverse, index) = self.revert(change);
+        self.redo_history.push(inverse);
+        self.is_dirty = true;
+        Some(index)
+    }
+
+    // replay the last undone change, returns the index of the affected task
+    pub fn redo(&mut self) -> Option<usize> {
+        let change = self.redo_history.pop()?;
+        let (inverse, index) = self.revert(change);
+        self.push_history(inverse);
+        self.is_dirty = true;
+        Some(index)
+    }
+
+    // apply the opposite of the change, returns the change that would revert it again
+    fn revert(&mut self, change: Change) -> (Change, usize) {
+        match change {
+            Change::Added(index) => {
+                let task = self.tasks.remove(index);
+                (Change::Deleted(index, task), index)
+            }
+            Change::Deleted(index, task) => {
+                self.tasks.insert(index, task);
+                (Change::Added(index), index)
+            }
+            Change::Moved(index, from) => {
+                let task = self.tasks.remove(index);
+                self.tasks.insert(from, task);
+                (Change::Moved(from, index), from)
+            }
+            Change::NotesChanged(index, notes) => {
+                let previous = std::mem::replace(&mut self.tasks[index].notes, notes);
+                (Change::NotesChanged(index, previous), index)
+            }
+            Change::DepthChanged(index, depth) => {
+                let previous = std::mem::replace(&mut self.tasks[index].depth, depth);
+                (Change::DepthChanged(index, previous), index)
+            }
+            Change::TextChanged(index, text) => {
+                let previous = self.tasks[index].set_text(text);
+                (Change::TextChanged(index, previous), index)
+            }
+            Change::Batch(changes) => {
+                let mut inverses = Vec::new();
+                let mut last_index = 0;
+                for change in changes.into_iter().rev() {
+                    let (inverse, index) = self.revert(change);
+                    inverses.push(inverse);
+                    last_index = index;
+                }
+                (Change::Batch(inverses), last_index)
+            }
+        }
+    }
+
+    pub fn counts(&self) -> TaskCounts {
+        let mut counts = TaskCounts::default();
+        for task in &self.tasks {
+            match task.task_type {
+                TaskType::Todo => counts.todo += 1,
+                TaskType::Doing => counts.doing += 1,
+                TaskType::Done => counts.done += 1,
+                TaskType::Rejected => counts.rejected += 1,
+                TaskType::NotDefined => {}
+            }
+        }
+        counts
+    }
+
+    // indices of the tasks carrying the tag, in file order
+    pub fn tasks_with_tag(&self, tag: &str) -> Vec<usize> {
+        (0..self.tasks.len())
+            .filter(|&i| self.tasks[i].has_tag(tag))
+            .collect()
+    }
+
+    // show only the doing tasks, or what was shown before again, returns whether focused now
+    pub fn toggle_focus(&mut self) -> bool {
+        match self.unfocused_types.take() {
+            Some(hidden_types) => {
+                self.hidden_types = hidden_types;
+                false
+            }
+            None => {
+                let focused = vec![TaskType::Todo, TaskType::Done, TaskType::Rejected];
+                self.unfocused_types = Some(std::mem::replace(&mut self.hidden_types, focused));
+                true
+            }
+        }
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.unfocused_types.is_some()
+    }
+
+    // show only the tasks for today or everything again, returns whether showing today now
+    pub fn toggle_today(&mut self) -> bool {
+        self.showing_today = !self.showing_today;
+        self.showing_today
+    }
+
+    pub fn is_showing_today(&self) -> bool {
+        self.showing_today
+    }
+
+    // show or hide the tasks with the status, returns whether they are shown now
+    pub fn toggle_type_visibility(&mut self, task_type: TaskType) -> bool {
+        match self
+            .hidden_types
+            .iter()
+            .position(|&hidden| hidden == task_type)
+        {
+            Some(position) => {
+                self.hidden_types.remove(position);
+                true
+            }
+            None => {
+                self.hidden_types.push(task_type);
+                false
+            }
+        }
+    }
+
+    pub fn matches_filter(&self, task: &Task) -> bool {
+        // headers only make sense above the whole of their section
+        if task.is_header() {
+            return self.filter.is_empty() && self.tag_filter.is_empty() && !self.showing_today;
+        }
+        // the today view shows its tasks whatever statuses are hidden
+        if self.showing_today {
+            if !is_for_today(task, Date::today()) {
+                return false;
+            }
+        } else if self.hidden_types.contains(&task.task_type) {
+            return false;
+        }
+        if !self.tag_filter.is_empty() && !task.has_tag(&self.tag_filter) {
+            return false;
+        }
+        self.filter.is_empty()
+            || task
+                .display_text()
+                .to_lowercase()
+                .contains(&self.filter.to_lowercase())
+    }
+
+    // indices of the tasks and headers in the order they are displayed, the tasks are
+    // sorted within the section below each header and the headers stay on top of them
+    pub fn view_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.tasks.len())
+            .filter(|&i| {
+                (self.tasks[i].is_header() || !self.tasks[i].is_comment())
+                    && self.matches_filter(&self.tasks[i])
+            })
+            .collect();
+        let mut sections = Vec::with_capacity(self.tasks.len());
+        let mut section = 0;
+        for task in &self.tasks {
+            if task.is_header() {
+                section += 1;
+            }
+            sections.push(section);
+        }
+        let place = |i: usize| (sections[i], !self.tasks[i].is_header());
+        match self.sort_mode {
+            // the position in the file is the order within a status, which saving keeps
+            SortMode::Status => order.sort_by_key(|&i| (place(i), self.tasks[i].task_type, i)),
+            SortMode::ActiveFirst => {
+                order.sort_by_key(|&i| (place(i), is_finished(&self.tasks[i])))
+            }
+            SortMode::Alphabetical => order.sort_by_key(|&i| {
+                (
+                    place(i),
+                    get_description(&self.tasks[i].text).to_lowercase(),
+                )
+            }),
+            SortMode::Insertion => {}
+        }
+        order
+    }
+
+    // index of the task displayed on the given 1-based row, None on a header
+    pub fn task_index(&self, row: u16) -> Option<usize> {
+        self.view_order()
+            .get((row as usize).checked_sub(1)?)
+            .copied()
+            .filter(|&index| !self.tasks[index].is_header())
+    }
+
+    // the closest row above or below the row with a task on it, headers are skipped
+    pub fn next_task_row(&self, row: u16, direction: Direction) -> Option<u16> {
+        let view_order = self.view_order();
+        let is_task = |row: u16| {
+            view_order
+                .get((row as usize).checked_sub(1)?)
+                .filter(|&&index| !self.tasks[index].is_header())
+                .map(|_| row)
+        };
+        match direction {
+            Direction::Up => (1..row).rev().find_map(is_task),
+            _ => (row + 1..=view_order.len() as u16).find_map(is_task),
+        }
+    }
+
+    // the 1-based rows with a task on them, i.e. every row but the headers
+    pub fn task_rows(&self) -> Vec<u16> {
+        self.view_order()
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, index)| !self.tasks[index].is_header())
+            .map(|(position, _)| position as u16 + 1)
+            .collect()
+    }
+
+    // 1-based position of the row among the rows with a task and the number of those rows,
+    // headers are not counted
+    pub fn task_position(&self, row: u16) -> (usize, usize) {
+        let rows = self.task_rows();
+        (
+            rows.iter().filter(|&&task_row| task_row <= row).count(),
+            rows.len(),
+        )
+    }
+
+    // 1-based row on which the task with the given index is displayed
+    pub fn task_row(&self, index: usize) -> u16 {
+        match self.view_order().iter().position(|&i| i == index) {
+            Some(position) => position as u16 + 1,
+            None => 1,
+        }
+    }
+
+    pub fn save(&mut self, file_path: &str) -> io::Result<()> {
+        let file = File::create(file_path)?;
+        let mut writer = BufWriter::new(file);
+        let format = self.format_for(file_path);
+        if format == FileFormat::Json {
+            writer.write_all(tasks_to_json(&self.tasks).as_bytes())?;
+        } else if format == FileFormat::Tsv {
+            writer.write_all(tasks_to_tsv(&self.tasks).as_bytes())?;
+        } else {
+            for task in &self.tasks {
+                let mut new_line = task.line() + &task.note_lines();
+                new_line.push('\n');
+                writer.write_all(new_line.as_bytes())?;
+            }
+        }
+        writer.flush()?;
+        self.is_dirty = false;
+        Ok(())
+    }
+}
+
+// status changes of list.txt are logged to list.txt.log
+pub fn log_path(file_path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.log", file_path))
+}
+
+// done tasks of list.txt are archived to list.archive.txt
+pub fn archive_path(file_path: &str) -> PathBuf {
+    let path = Path::new(file_path);
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}.archive.txt", stem))
+}
+
+// the tasks of an archive file as listed by the restore picker
+pub fn archived_tasks(archive_path: &Path) -> io::Result<Vec<Task>> {
+    let archived = tasks_from_text(&String::from_utf8_lossy(&fs::read(archive_path)?));
+    Ok(archive_positions(&archived)
+        .into_iter()
+        .map(|position| archived[position].clone())
+        .collect())
+}
+
+// positions of the lines of an archive file shown by the picker, blank lines, comments
+// and headers are skipped
+fn archive_positions(archived: &[Task]) -> Vec<usize> {
+    (0..archived.len())
+        .filter(|&i| !archived[i].is_comment())
+        .collect()
+}
+
+// the lines of a text list, the indented ones below a task are its notes
+fn tasks_from_text(content: &str) -> Vec<Task> {
+    let mut tasks: Vec<Task> = Vec::new();
+    for line in content.lines() {
+        // a stray \r from Windows line endings or trailing spaces would end up in the text
+        let line = line.trim_end();
+        if is_note_line(line) {
+            if let Some(task) = tasks.last_mut().filter(|task| !task.is_comment()) {
+                if !task.notes.is_empty() {
+                    task.notes.push('\n');
+                }
+                task.notes.push_str(unescape_note(line.trim_start()));
+                continue;
+            }
+        }
+        tasks.push(Task::new(line, get_type_from_string(line)));
+    }
+    tasks
+}
+
+fn is_for_today(task: &Task, today: Date) -> bool {
+    task.task_type == TaskType::Doing || task.due == Some(today)
+}
+
+// done and rejected tasks need nothing more, lines without a status count as active
+fn is_finished(task: &Task) -> bool {
+    matches!(task.task_type, TaskType::Done | TaskType::Rejected)
+}
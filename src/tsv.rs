@@ -0,0 +1,55 @@
+use crate::task::{
+    get_type_from_string, is_comment_line, type_to_string, unescape_note, Task, TaskType, INDENT,
+};
+
+// one task per line as a status keyword, a tab and the text, e.g. "done\tbuy milk";
+// subtasks and notes are indented like in the plain text format
+pub fn tasks_to_tsv(tasks: &[Task]) -> String {
+    let mut tsv = String::new();
+    for task in tasks {
+        if task.task_type == TaskType::NotDefined {
+            tsv.push_str(&task.line());
+        } else {
+            tsv.push_str(&INDENT.repeat(task.depth));
+            tsv.push_str(task.task_type.as_keyword());
+            tsv.push('\t');
+            tsv.push_str(task.display_text());
+        }
+        tsv.push_str(&task.note_lines());
+        tsv.push('\n');
+    }
+    tsv
+}
+
+pub fn tasks_from_tsv(tsv: &str) -> Vec<Task> {
+    let mut tasks: Vec<Task> = Vec::new();
+    for line in tsv.lines() {
+        let line = line.trim_end();
+        let text = line.trim_start_matches([' ', '\t']);
+        let indentation = &line[..line.len() - text.len()];
+        if let Some((keyword, description)) = text.split_once('\t') {
+            if let Some(task_type) = TaskType::from_keyword(keyword) {
+                let text = format!(
+                    "{}{} {}",
+                    indentation,
+                    type_to_string(task_type),
+                    description.trim_start()
+                );
+                tasks.push(Task::new(&text, task_type));
+                continue;
+            }
+        }
+        // indented lines without a status belong to the task above them
+        if !indentation.is_empty() && !is_comment_line(text) {
+            if let Some(task) = tasks.last_mut().filter(|task| !task.is_comment()) {
+                if !task.notes.is_empty() {
+                    task.notes.push('\n');
+                }
+                task.notes.push_str(unescape_note(text));
+                continue;
+            }
+        }
+        tasks.push(Task::new(line, get_type_from_string(line)));
+    }
+    tasks
+}
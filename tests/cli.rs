@@ -0,0 +1,294 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+// a list file in the system temp dir for one test, removed again afterwards
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn new(name: &str, content: &str) -> TempFile {
+        let dir = std::env::temp_dir().join(format!("todo-cli-tests-{}", std::process::id()));
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        TempFile { path }
+    }
+
+    fn path(&self) -> &str {
+        self.path.to_str().unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// the binary with an empty home and none of the variables of whoever runs the tests, so a
+// config file or TODO_FILE on the machine cannot change the output
+fn command() -> Command {
+    let home = std::env::temp_dir().join(format!("todo-cli-tests-{}-home", std::process::id()));
+    fs::create_dir_all(&home).unwrap();
+    let mut command = Command::new(env!("CARGO_BIN_EXE_todo"));
+    command
+        .env("HOME", &home)
+        .env("XDG_CONFIG_HOME", home.join(".config"))
+        .env_remove("NO_COLOR");
+    for (name, _) in std::env::vars_os() {
+        if name.to_string_lossy().starts_with("TODO_") {
+            command.env_remove(name);
+        }
+    }
+    command
+}
+
+fn todo(args: &[&str]) -> (i32, String) {
+    let output = command().args(args).output().unwrap();
+    (
+        output.status.code().unwrap_or(-1),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn json_prints_the_status_and_text_of_every_task() {
+    let content = "# heading\n[ ] ! write \"docs\" created:2024-01-01\n[X] ship\n";
+    let file = TempFile::new("json.txt", content);
+
+    let (code, stdout) = todo(&[file.path(), "json"]);
+
+    assert_eq!(code, 0);
+    assert_eq!(
+        stdout,
+        "[\n  {\"status\": \"todo\", \"text\": \"write \\\"docs\\\"\"},\n  {\"status\": \"done\", \"text\": \"ship\"}\n]\n"
+    );
+    assert_eq!(fs::read_to_string(&file.path).unwrap(), content);
+}
+
+#[test]
+fn json_of_an_empty_list_is_an_empty_array() {
+    let file = TempFile::new("json_empty.txt", "");
+
+    let (code, stdout) = todo(&[file.path(), "json"]);
+
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "[]\n");
+}
+
+#[test]
+fn print_shows_the_sorted_list_and_leaves_the_file_alone() {
+    let content = "[X] ship\n# heading\n[ ] write docs\n  [+] outline\n";
+    let file = TempFile::new("print.txt", content);
+
+    let (code, stdout) = todo(&[file.path(), "--print", "--no-color"]);
+
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "[ ] write docs\n  [+] outline\n[X] ship\n");
+    assert_eq!(fs::read_to_string(&file.path).unwrap(), content);
+}
+
+#[test]
+fn print_colors_the_tasks_unless_told_not_to() {
+    let file = TempFile::new("print_color.txt", "[ ] task\n");
+
+    let (_, stdout) = todo(&[file.path(), "--print"]);
+
+    assert!(stdout.contains("\x1b["));
+    assert!(stdout.ends_with('\n'));
+}
+
+#[test]
+fn todo_file_is_used_when_no_path_is_given() {
+    let file = TempFile::new("env.txt", "[ ] from the environment\n");
+
+    let output = command()
+        .arg("json")
+        .env("TODO_FILE", file.path())
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "[\n  {\"status\": \"todo\", \"text\": \"from the environment\"}\n]\n"
+    );
+}
+
+#[test]
+fn a_path_argument_wins_over_todo_file() {
+    let file = TempFile::new("argument.txt", "[X] from the argument\n");
+    let other = TempFile::new("ignored.txt", "[ ] from the environment\n");
+
+    let output = command()
+        .args([file.path(), "json"])
+        .env("TODO_FILE", other.path())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("from the argument"));
+    assert!(!stdout.contains("from the environment"));
+}
+
+#[test]
+fn without_a_path_or_todo_file_it_asks_for_one() {
+    let output = command().output().unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Please provide a path"));
+}
+
+#[test]
+fn format_tsv_reads_keyword_lines() {
+    let file = TempFile::new("format.tsv", "doing\tship it\n");
+
+    let (code, stdout) = todo(&["--format", "tsv", file.path(), "json"]);
+
+    assert_eq!(code, 0);
+    assert_eq!(
+        stdout,
+        "[\n  {\"status\": \"doing\", \"text\": \"ship it\"}\n]\n"
+    );
+}
+
+#[test]
+fn without_a_terminal_the_list_is_printed_instead() {
+    let file = TempFile::new("no_tty.txt", "[ ] task\n");
+
+    let output = command()
+        .args([file.path(), "--no-color"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "[ ] task\n");
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Not running in a terminal"));
+}
+
+#[test]
+fn the_list_printed_into_a_pipe_has_no_colors() {
+    let file = TempFile::new("no_tty_colors.txt", "[ ] task #home\n[X] done\n");
+
+    let (code, stdout) = todo(&[file.path()]);
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "[ ] task #home\n[X] done\n");
+
+    let (_, printed) = todo(&[file.path(), "--print"]);
+    assert!(printed.contains('\x1b'));
+}
+
+#[test]
+fn text_starting_with_a_dash_can_be_added() {
+    let file = TempFile::new("dashes.txt", "");
+
+    let (code, _) = todo(&[file.path(), "add", "--", "-5 degrees"]);
+    assert_eq!(code, 0);
+    let (code, _) = todo(&[file.path(), "add", "--fix the bug"]);
+    assert_eq!(code, 0);
+
+    let content = fs::read_to_string(&file.path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("[ ] -5 degrees created:"));
+    assert!(lines[1].starts_with("[ ] --fix the bug created:"));
+}
+
+#[test]
+fn every_word_after_the_path_is_opened_as_a_file() {
+    let list = TempFile::new("list.txt", "[ ] a\n");
+    let notes = TempFile::new("notes", "[+] b\n");
+
+    let output = command()
+        .current_dir(list.path.parent().unwrap())
+        .args(["list.txt", "notes", "--no-color"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "list.txt:\n[ ] a\nnotes:\n[+] b\n"
+    );
+    drop(notes);
+
+    let output = command()
+        .args([list.path(), list.path(), "add", "c"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("The add command goes right after the path"));
+}
+
+#[test]
+fn an_unknown_option_is_refused() {
+    let file = TempFile::new("option.txt", "[ ] a\n");
+
+    let (code, stdout) = todo(&[file.path(), "--bogus"]);
+
+    assert_eq!(code, 2);
+    assert!(stdout.is_empty());
+}
+
+// the text with the ANSI escape sequences taken out
+fn strip_escapes(text: &str) -> String {
+    let mut plain = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // a CSI sequence ends with its first letter
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+#[test]
+fn colored_and_plain_output_differ_only_by_escape_sequences() {
+    let content = "--- Home ---\n[ ] buy #milk due:2000-01-01\n[+] cook\n[X] wash\n[-] iron\n";
+    let file = TempFile::new("colors.txt", content);
+
+    let (_, colored) = todo(&["--print", file.path()]);
+    let (_, plain) = todo(&["--print", "--no-color", file.path()]);
+
+    assert_ne!(colored, plain);
+    assert!(!plain.contains('\x1b'));
+    assert_eq!(strip_escapes(&colored), plain);
+}
+
+#[test]
+fn the_help_shows_the_keys_of_the_config_file() {
+    let config = TempFile::new(
+        "remapped/todo-rust/config.toml",
+        "[keys]\nundo = \"ctrl-z\"\n",
+    );
+
+    let output = command()
+        .arg("--help")
+        .env(
+            "XDG_CONFIG_HOME",
+            config.path.parent().unwrap().parent().unwrap(),
+        )
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout
+        .lines()
+        .any(|line| line.starts_with("  ctrl-z") && line.ends_with("undo the last change")));
+    let (_, default) = todo(&["--help"]);
+    assert!(default
+        .lines()
+        .any(|line| line.starts_with("  u ") && line.ends_with("undo the last change")));
+}
@@ -0,0 +1,165 @@
+use termion::event::Key;
+use todo::config::{Config, AUTOSAVE_SECONDS};
+use todo::keymap::{parse_key, Action, KeyMap, ACTION_NAMES};
+use todo::todo_list::SortMode;
+use todo::TaskType;
+
+#[test]
+fn an_empty_file_keeps_every_default() {
+    let config = Config::parse("");
+
+    assert!(config.sort_mode == SortMode::Status);
+    assert_eq!(config.autosave_seconds, AUTOSAVE_SECONDS);
+    assert!(config.cycle.is_none());
+    assert_eq!(config.theme.done_glyph, "[X]");
+    assert!(config.warnings.is_empty());
+}
+
+#[test]
+fn settings_left_out_keep_their_defaults() {
+    let config = Config::parse(
+        "# my settings\n\
+         sort = \"open\"  # finished tasks last\n\
+         \n\
+         [glyphs]\n\
+         done = \"✓\"\n",
+    );
+
+    assert!(config.sort_mode == SortMode::ActiveFirst);
+    assert_eq!(config.autosave_seconds, AUTOSAVE_SECONDS);
+    assert_eq!(config.theme.done_glyph, "✓");
+    assert_eq!(config.theme.todo_glyph, "[ ]");
+    assert!(config.warnings.is_empty());
+}
+
+#[test]
+fn every_setting_is_read() {
+    let config = Config::parse(
+        "sort = \"insertion\"\n\
+         autosave = 0\n\
+         cycle = \"todo,done\"\n\
+         [colors]\n\
+         tag = \"bright-cyan\"\n\
+         [glyphs]\n\
+         doing = \"#\"\n",
+    );
+
+    assert!(config.sort_mode == SortMode::Insertion);
+    assert_eq!(config.autosave_seconds, 0);
+    assert_eq!(config.cycle, Some(vec![TaskType::Todo, TaskType::Done]));
+    assert_eq!(config.theme.tag as u8, 96);
+    assert_eq!(config.theme.doing_glyph, "#");
+}
+
+#[test]
+fn invalid_lines_are_warnings_and_the_rest_still_applies() {
+    let config = Config::parse(
+        "sort = status\n\
+         autosave = soon\n\
+         nonsense\n\
+         [colors]\n\
+         todo = \"purple\"\n\
+         done = \"cyan\"\n\
+         [sounds]\n\
+         done = \"bell\"\n",
+    );
+
+    assert_eq!(config.warnings.len(), 5);
+    assert!(config.warnings[0].starts_with("config.toml line 1:"));
+    assert!(config.sort_mode == SortMode::Status);
+    assert_eq!(config.autosave_seconds, AUTOSAVE_SECONDS);
+    assert_eq!(config.theme.todo as u8, 34);
+    assert_eq!(config.theme.done as u8, 36);
+}
+
+#[test]
+fn a_remapped_key_triggers_its_action() {
+    let config = Config::parse("[keys]\ncycle-status = \"space ctrl-n\"\n");
+
+    assert!(config.warnings.is_empty());
+    let keymap = &config.keymap;
+    assert_eq!(keymap.action(&Key::Char(' ')), Some(Action::CycleStatus));
+    assert_eq!(keymap.action(&Key::Ctrl('n')), Some(Action::CycleStatus));
+    // the default keys of the action are free again
+    assert_eq!(keymap.action(&Key::Right), None);
+    assert_eq!(keymap.action(&Key::Char('l')), None);
+    assert_eq!(keymap.action(&Key::Left), Some(Action::StopCycling));
+}
+
+#[test]
+fn the_shifted_arrows_are_bound_like_the_other_keys() {
+    let keymap = KeyMap::default();
+    assert_eq!(keymap.action(&Key::ShiftUp), Some(Action::RaisePriority));
+    assert_eq!(keymap.action(&Key::ShiftDown), Some(Action::LowerPriority));
+    assert_eq!(
+        keymap.action(&Key::ShiftRight),
+        Some(Action::CycleStatusBack)
+    );
+
+    let config = Config::parse("[keys]\nraise-priority = \"+\"\nlower-priority = \"shift-up\"\n");
+    assert!(config.warnings.is_empty());
+    assert_eq!(
+        config.keymap.action(&Key::Char('+')),
+        Some(Action::RaisePriority)
+    );
+    assert_eq!(
+        config.keymap.action(&Key::ShiftUp),
+        Some(Action::LowerPriority)
+    );
+    assert_eq!(config.keymap.action(&Key::ShiftDown), None);
+}
+
+#[test]
+fn taking_the_key_of_another_action_is_a_warning() {
+    let config = Config::parse("[keys]\ndelete = \"x\"\nundo = \"x\"\nfly = \"w\"\n");
+
+    assert_eq!(
+        config.warnings,
+        [
+            "config.toml line 2: x no longer does set-done",
+            "config.toml line 3: x no longer does delete",
+            "config.toml line 4: unknown action fly",
+        ]
+    );
+    assert_eq!(config.keymap.action(&Key::Char('x')), Some(Action::Undo));
+    assert_eq!(config.keymap.action(&Key::Char('d')), None);
+}
+
+#[test]
+fn keys_are_named_like_in_the_config_file() {
+    assert_eq!(parse_key("q"), Some(Key::Char('q')));
+    assert_eq!(parse_key("Q"), Some(Key::Char('Q')));
+    assert_eq!(parse_key("Enter"), Some(Key::Char('\n')));
+    assert_eq!(parse_key("ctrl-r"), Some(Key::Ctrl('r')));
+    assert_eq!(parse_key("alt-X"), Some(Key::Alt('X')));
+    assert_eq!(parse_key("f5"), Some(Key::F(5)));
+    assert_eq!(parse_key("f13"), None);
+    assert_eq!(parse_key("pagedown"), Some(Key::PageDown));
+    assert_eq!(parse_key("hyper"), None);
+    assert_eq!(
+        KeyMap::default().action(&Key::Char('q')),
+        Some(Action::Quit)
+    );
+}
+
+#[test]
+fn the_help_lists_the_keys_of_the_config_file() {
+    let help = KeyMap::default().help();
+    assert!(help.contains(&("u".to_string(), "undo the last change")));
+    assert!(help.contains(&("up, k / down, j".to_string(), "move the cursor")));
+
+    let config = Config::parse("[keys]\nundo = \"ctrl-z u\"\ndelete = \"\"\n");
+    let help = config.keymap.help();
+    assert!(help.contains(&("u, ctrl-z".to_string(), "undo the last change")));
+    assert!(help.contains(&("unbound".to_string(), "delete the selected task")));
+    // every action is listed
+    for &(name, action) in ACTION_NAMES {
+        let mut keymap = KeyMap::default();
+        keymap.rebind(action, &[Key::F(12)]);
+        assert!(
+            keymap.help().iter().any(|(keys, _)| keys.contains("f12")),
+            "{} is missing from the help",
+            name
+        );
+    }
+}
@@ -0,0 +1,376 @@
+use todo::color::{next_override_color, ConsoleBackgroundColors, ConsoleForegroundColors, Theme};
+use todo::console::{find_matches, fit_line, get_tagged_text, remaining_summary, Console};
+use todo::date::Date;
+use todo::todo_list::Direction;
+use todo::{get_type_from_string, Task, TaskType, TodoList};
+
+fn list(lines: &[&str]) -> TodoList {
+    let mut todo_list = TodoList::new();
+    for line in lines {
+        todo_list
+            .tasks
+            .push(Task::new(line, get_type_from_string(line)));
+    }
+    todo_list
+}
+
+#[test]
+fn every_row_is_drawn_from_the_left_edge() {
+    let mut todo_list = list(&["[ ] a", "[+] b", "[X] c", "[-] d", "[ ] e"]);
+    let mut console = Console::new();
+
+    let frame = todo_list.render(&mut console);
+
+    // a bare newline would not return the cursor in raw mode, so the rows would stairstep
+    assert!(!frame.contains('\n'));
+    for row in 1..=5 {
+        let goto = format!("\x1b[{};1H", console.screen_row(row));
+        assert!(
+            frame.contains(&goto),
+            "row {} is not drawn at column 1",
+            row
+        );
+    }
+}
+
+#[test]
+fn matches_are_found_case_insensitively() {
+    assert_eq!(
+        find_matches("Buy milk, then BUY bread", "buy"),
+        [(0, 3), (15, 18)]
+    );
+    assert_eq!(find_matches("aaaa", "aa"), [(0, 2), (2, 4)]);
+    assert!(find_matches("milk", "").is_empty());
+    assert!(find_matches("milk", "bread").is_empty());
+}
+
+#[test]
+fn matches_respect_multibyte_characters() {
+    assert_eq!(find_matches("Žluť žluť", "ŽLUŤ"), [(0, 6), (7, 13)]);
+}
+
+#[test]
+fn search_matches_are_highlighted_in_the_list() {
+    let mut todo_list = list(&["[ ] buy Milk", "[ ] milk the cow", "[ ] bread"]);
+    todo_list.filter = "milk".to_string();
+    let mut console = Console::new();
+
+    let frame = todo_list.render(&mut console);
+
+    assert!(frame.contains("\x1b[30;43mMilk\x1b[0m"));
+    assert!(frame.contains("\x1b[30;43mmilk\x1b[0m"));
+    assert!(!frame.contains("bread"));
+}
+
+#[test]
+fn only_the_description_is_highlighted() {
+    let mut todo_list = list(&["[ ] do laundry", "  [+] dough created:2024-01-01"]);
+    todo_list.filter = "do".to_string();
+    todo_list.is_editing = true;
+    let mut console = Console::new();
+
+    let frame = todo_list.render(&mut console);
+
+    // not in the "(Current: Todo, Next: Doing)" of the task being cycled
+    assert_eq!(frame.matches("\x1b[30;43m").count(), 2);
+    assert!(frame.contains("\x1b[30;43mdo\x1b[0m"));
+}
+
+#[test]
+fn long_lines_are_cut_with_a_marker_on_the_cut_side() {
+    assert_eq!(fit_line("short", 0, 10), "short");
+    assert_eq!(fit_line("0123456789", 0, 6), "01234>");
+    assert_eq!(fit_line("0123456789", 2, 6), "<3456>");
+    assert_eq!(fit_line("0123456789", 4, 6), "<56789");
+    // the end of the line stays on screen however far it is scrolled
+    assert_eq!(fit_line("0123456789", 50, 6), "<56789");
+    assert_eq!(fit_line("žluťoučký kůň", 1, 6), "<uťou>");
+}
+
+#[test]
+fn only_the_selected_task_scrolls() {
+    let long = format!("[ ] {}end", "x".repeat(100));
+    let mut todo_list = list(&[long.as_str(), long.as_str()]);
+    let mut console = Console::new();
+    console.terminal_size = (40, 10);
+    console.horizontal_scroll = Some(1000);
+
+    let frame = todo_list.render(&mut console);
+
+    assert_eq!(console.horizontal_scroll, Some(67));
+    assert!(frame.contains(&format!("<{}end", "x".repeat(36))));
+    assert!(frame.contains(&format!("\x1b[34m[ ] {}>", "x".repeat(35))));
+    assert_eq!(frame.matches("end").count(), 1);
+}
+
+#[test]
+fn the_today_view_replaces_the_file_name_in_the_header() {
+    let mut todo_list = list(&["[+] a"]);
+    let mut console = Console::new();
+    console.header = Some("list.txt".to_string());
+    todo_list.toggle_today();
+
+    let frame = todo_list.render(&mut console);
+
+    assert!(frame.contains("TODAY"));
+    assert!(!frame.contains("list.txt"));
+}
+
+#[test]
+fn the_summary_counts_the_open_tasks_in_their_colors() {
+    let todo_list = list(&["[ ] a", "[+] b", "[ ] c", "[X] d", "# e", "[ ] f"]);
+    let theme = Theme::new();
+
+    assert_eq!(
+        remaining_summary(&todo_list.counts(), "list.txt", &theme),
+        "\x1b[34m3 todo\x1b[0m, \x1b[35m1 doing\x1b[0m remaining in list.txt"
+    );
+    let todo_list = list(&["[X] a", "[-] b"]);
+    assert_eq!(
+        remaining_summary(&todo_list.counts(), "list.txt", &theme),
+        "Nothing remaining in list.txt"
+    );
+}
+
+#[test]
+fn a_color_override_wins_over_the_status_color() {
+    let todo_list = list(&["[ ] call color:red", "[ ] mom"]);
+
+    let text = todo_list.to_colored_text(&Theme::new());
+
+    // the marker itself is not drawn
+    assert_eq!(text, "\x1b[31m[ ] call\x1b[0m\n\x1b[34m[ ] mom\x1b[0m\n");
+}
+
+#[test]
+fn the_override_colors_are_cycled_back_to_none() {
+    let mut color = None;
+    let mut seen = Vec::new();
+    loop {
+        color = next_override_color(color);
+        match color {
+            Some(color) => seen.push(color),
+            None => break,
+        }
+    }
+    assert_eq!(seen.len(), 6);
+    assert_eq!(seen[0], ConsoleForegroundColors::Red);
+    assert_eq!(
+        next_override_color(Some(ConsoleForegroundColors::BrightRed)),
+        None
+    );
+}
+
+#[test]
+fn page_keys_move_the_cursor_and_the_list_by_a_screen() {
+    let lines: Vec<String> = (0..20).map(|i| format!("[ ] task {}", i)).collect();
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let mut todo_list = list(&lines);
+    let mut console = Console::new();
+    console.terminal_size = (40, 10);
+
+    // eight rows fit, so twenty tasks make three pages
+    console.page(Direction::Down, 20);
+    assert_eq!((console.cursor_position.1, console.scroll_offset), (9, 8));
+    assert!(todo_list.render(&mut console).contains("page 2/3"));
+
+    console.page(Direction::Down, 20);
+    console.page(Direction::Down, 20);
+    assert_eq!((console.cursor_position.1, console.scroll_offset), (20, 12));
+    assert!(todo_list.render(&mut console).contains("page 3/3"));
+
+    console.page(Direction::Up, 20);
+    console.page(Direction::Up, 20);
+    console.page(Direction::Up, 20);
+    assert_eq!((console.cursor_position.1, console.scroll_offset), (1, 0));
+    assert!(todo_list.render(&mut console).contains("page 1/3"));
+}
+
+#[test]
+fn a_list_that_fits_has_no_page_indicator() {
+    let mut todo_list = list(&["[ ] a", "[ ] b"]);
+    let mut console = Console::new();
+    console.terminal_size = (40, 10);
+
+    console.page(Direction::Down, 2);
+
+    assert_eq!(console.cursor_position.1, 2);
+    assert!(!todo_list.render(&mut console).contains("page"));
+}
+
+#[test]
+fn a_save_shows_in_green_and_a_failure_in_red() {
+    let mut todo_list = list(&["[ ] a"]);
+    let mut console = Console::new();
+
+    console.notice = Some("Saved ✓".to_string());
+    assert!(todo_list.render(&mut console).contains("\x1b[32mSaved ✓"));
+
+    console.notice = None;
+    console.message = Some("Save failed: denied".to_string());
+    let frame = todo_list.render(&mut console);
+    assert!(frame.contains("\x1b[31mSave failed: denied"));
+    assert!(!frame.contains("Saved"));
+}
+
+#[test]
+fn jumping_goes_to_the_typed_row_clamped_to_the_list() {
+    let lines: Vec<String> = (0..20).map(|i| format!("[ ] task {}", i)).collect();
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let mut todo_list = list(&lines);
+    let mut console = Console::new();
+    console.terminal_size = (40, 10);
+
+    assert!(console.jump_to(" 17 ", &todo_list.task_rows()));
+    todo_list.render(&mut console);
+    assert_eq!((console.cursor_position.1, console.scroll_offset), (17, 9));
+
+    assert!(console.jump_to("99", &todo_list.task_rows()));
+    assert_eq!(console.cursor_position.1, 20);
+    assert!(console.jump_to("0", &todo_list.task_rows()));
+    assert_eq!(console.cursor_position.1, 1);
+
+    assert!(!console.jump_to("seventeen", &todo_list.task_rows()));
+    assert!(!console.jump_to("", &todo_list.task_rows()));
+    assert_eq!(console.cursor_position.1, 1);
+}
+
+#[test]
+fn right_on_an_empty_list_finds_no_task_to_change() {
+    let mut todo_list = TodoList::new();
+    let mut console = Console::new();
+
+    assert!(!todo_list.cycle_at_cursor(&mut console));
+    todo_list.render(&mut console);
+
+    assert!(todo_list.tasks.is_empty());
+    assert!(!todo_list.is_dirty);
+}
+
+#[test]
+fn pressing_down_past_the_last_task_stays_on_it() {
+    let mut todo_list = list(&["[ ] a", "[ ] b", "[ ] c"]);
+    let mut console = Console::new();
+
+    for _ in 0..100 {
+        todo_list.step_cursor(&mut console, Direction::Down);
+    }
+    todo_list.render(&mut console);
+
+    assert_eq!(console.cursor_position.1, 3);
+    assert_eq!(todo_list.task_index(console.cursor_position.1), Some(2));
+}
+
+#[test]
+fn up_and_down_skip_the_headers_and_right_cycles_the_task_under_the_cursor() {
+    let mut todo_list = list(&["--- Home ---", "[ ] a", "--- Work ---", "[ ] b"]);
+    let mut console = Console::new();
+    console.cursor_position.1 = 2;
+    console.horizontal_scroll = Some(12);
+
+    todo_list.step_cursor(&mut console, Direction::Down);
+    assert_eq!(console.cursor_position.1, 4);
+    assert_eq!(console.horizontal_scroll, Some(0));
+    todo_list.step_cursor(&mut console, Direction::Up);
+    todo_list.step_cursor(&mut console, Direction::Up);
+    assert_eq!(console.cursor_position.1, 2);
+
+    assert!(todo_list.cycle_at_cursor(&mut console));
+    assert_eq!(todo_list.tasks[1].task_type, TaskType::Doing);
+    assert!(todo_list.is_editing);
+    assert_eq!(console.cursor_position.1, 2);
+}
+
+#[test]
+fn jumping_counts_only_the_tasks_under_the_headers() {
+    let mut todo_list = list(&["--- Home ---", "[ ] a", "[ ] b", "--- Work ---", "[ ] c"]);
+    let mut console = Console::new();
+    let rows = todo_list.task_rows();
+    assert_eq!(rows, [2, 3, 5]);
+
+    assert!(console.jump_to("3", &rows));
+    assert_eq!(todo_list.task_index(console.cursor_position.1), Some(4));
+    assert!(console.jump_to("1", &rows));
+    assert_eq!(todo_list.task_index(console.cursor_position.1), Some(1));
+    assert!(console.jump_to("9", &rows));
+    assert_eq!(console.cursor_position.1, 5);
+    assert!(todo_list.render(&mut console).contains("3/3"));
+
+    let empty = list(&["--- Home ---"]);
+    assert!(console.jump_to("2", &empty.task_rows()));
+    assert_eq!(console.cursor_position.1, 1);
+}
+
+#[test]
+fn a_cursor_past_the_list_or_the_screen_is_brought_back() {
+    let mut todo_list = list(&["[ ] a", "[ ] b", "[ ] c"]);
+    let mut console = Console::new();
+    console.terminal_size = (40, 10);
+    console.cursor_position = (500, 100);
+
+    todo_list.render(&mut console);
+
+    assert_eq!(console.cursor_position, (40, 3));
+}
+
+#[test]
+fn the_counter_and_the_pages_count_only_the_tasks() {
+    let mut lines = vec!["--- Home ---".to_string()];
+    lines.extend((0..8).map(|i| format!("[ ] home {}", i)));
+    lines.push("--- Work ---".to_string());
+    lines.extend((0..8).map(|i| format!("[ ] work {}", i)));
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let mut todo_list = list(&lines);
+    let mut console = Console::new();
+    console.terminal_size = (60, 10);
+
+    // the first task is on row 2, below the first header
+    let frame = todo_list.render(&mut console);
+    assert_eq!(console.cursor_position.1, 2);
+    assert!(frame.contains("1/16"));
+    assert!(frame.contains("page 1/2"));
+
+    // the last task is on row 18 of the view but the 16th task
+    console.cursor_position.1 = 18;
+    let frame = todo_list.render(&mut console);
+    assert!(frame.contains("16/16"));
+    assert!(frame.contains("page 2/2"));
+    assert_eq!(todo_list.task_position(10), (8, 16));
+    assert_eq!(todo_list.task_position(11), (9, 16));
+}
+
+#[test]
+fn only_open_tasks_past_their_due_date_are_overdue() {
+    let mut todo_list = list(&[
+        "[ ] open due:2000-01-01",
+        "[X] done due:2000-01-01",
+        "[-] rejected due:2000-01-01",
+    ]);
+    let today = Date::today();
+
+    assert!(todo_list.tasks[0].is_overdue(today));
+    assert!(!todo_list.tasks[1].is_overdue(today));
+    assert!(!todo_list.tasks[2].is_overdue(today));
+    // the cursor highlight would hide the red background of the first row
+    let mut console = Console::new();
+    console.cursor_position.1 = 3;
+    let frame = todo_list.render(&mut console);
+    assert!(frame.contains(";41m[ ] open"));
+    assert!(!frame.contains(";41m[X] done"));
+    assert!(!frame.contains(";41m[-] rejected"));
+}
+
+#[test]
+fn only_the_tags_are_colored_apart_from_the_rest_of_the_line() {
+    let colored = get_tagged_text(
+        ConsoleForegroundColors::Blue,
+        ConsoleBackgroundColors::None,
+        "buy milk #shop now #home",
+        ConsoleForegroundColors::Cyan,
+    );
+
+    assert_eq!(
+        colored,
+        "\x1b[34mbuy milk \x1b[0m\x1b[36m#shop\x1b[0m\x1b[34m now \x1b[0m\x1b[36m#home\x1b[0m"
+    );
+}
@@ -0,0 +1,32 @@
+use todo::date::{humanize_duration, Date};
+
+#[test]
+fn durations_are_shown_in_their_largest_whole_unit() {
+    for (seconds, expected) in [
+        (0, "just now"),
+        (59, "just now"),
+        (60, "1m"),
+        (5 * 60 + 59, "5m"),
+        (59 * 60 + 59, "59m"),
+        (60 * 60, "1h"),
+        (23 * 60 * 60, "23h"),
+        (24 * 60 * 60 - 1, "23h"),
+        (24 * 60 * 60, "1d"),
+        (6 * 24 * 60 * 60, "6d"),
+        (7 * 24 * 60 * 60 - 1, "6d"),
+        (7 * 24 * 60 * 60, "1w"),
+        (20 * 24 * 60 * 60, "2w"),
+    ] {
+        assert_eq!(humanize_duration(seconds), expected, "{} seconds", seconds);
+    }
+}
+
+#[test]
+fn ages_are_counted_in_days_and_weeks() {
+    let today = Date::parse("2024-03-01").unwrap();
+
+    assert_eq!(today.age(today), "today");
+    assert_eq!(Date::parse("2024-02-28").unwrap().age(today), "2d ago");
+    assert_eq!(Date::parse("2024-02-16").unwrap().age(today), "2w ago");
+    assert_eq!(Date::parse("2024-03-02").unwrap().age(today), "today");
+}
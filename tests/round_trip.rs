@@ -0,0 +1,675 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use todo::color::ConsoleForegroundColors;
+use todo::date::Date;
+use todo::json::{tasks_from_json, tasks_to_json};
+use todo::markdown::{from_markdown, to_markdown};
+use todo::task::Priority;
+use todo::todo_list::{archived_tasks, FileFormat, SortMode};
+use todo::{TaskType, TodoList};
+
+// a file in the system temp dir, removed again when the test is done with it
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn new(name: &str, content: Option<&str>) -> TempFile {
+        let dir = std::env::temp_dir().join(format!("todo-tests-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let _ = fs::remove_file(&path);
+        if let Some(content) = content {
+            fs::write(&path, content).unwrap();
+        }
+        TempFile { path }
+    }
+
+    fn path(&self) -> &str {
+        self.path.to_str().unwrap()
+    }
+
+    fn content(&self) -> String {
+        fs::read_to_string(&self.path).unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn load(file: &TempFile) -> TodoList {
+    let mut todo_list = TodoList::new();
+    todo_list.load(file.path()).unwrap();
+    todo_list
+}
+
+#[test]
+fn every_status_prefix_survives_a_round_trip() {
+    let content = "[ ] todo\n[+] doing\n[X] done\n[-] rejected\n";
+    let file = TempFile::new("statuses.txt", Some(content));
+    let mut todo_list = load(&file);
+
+    let types: Vec<TaskType> = todo_list.tasks.iter().map(|task| task.task_type).collect();
+    assert_eq!(
+        types,
+        vec![
+            TaskType::Todo,
+            TaskType::Doing,
+            TaskType::Done,
+            TaskType::Rejected
+        ]
+    );
+
+    todo_list.save(file.path()).unwrap();
+    assert_eq!(file.content(), content);
+}
+
+#[test]
+fn lines_without_a_status_are_not_defined_and_kept() {
+    let content = "just some text\n[?] odd marker\n";
+    let file = TempFile::new("not_defined.txt", Some(content));
+    let mut todo_list = load(&file);
+
+    assert!(todo_list
+        .tasks
+        .iter()
+        .all(|task| task.task_type == TaskType::NotDefined));
+
+    todo_list.save(file.path()).unwrap();
+    assert_eq!(file.content(), content);
+}
+
+#[test]
+fn empty_file_has_no_tasks() {
+    let file = TempFile::new("empty.txt", Some(""));
+    let mut todo_list = load(&file);
+
+    assert!(todo_list.tasks.is_empty());
+
+    todo_list.save(file.path()).unwrap();
+    assert_eq!(file.content(), "");
+}
+
+#[test]
+fn missing_file_is_created_empty() {
+    let file = TempFile::new("missing.txt", None);
+    let todo_list = load(&file);
+
+    assert!(todo_list.tasks.is_empty());
+    assert_eq!(file.content(), "");
+}
+
+#[test]
+fn comments_and_blank_lines_stay_in_place() {
+    let content = "# work\n[ ] first\n\n# home\n[+] second\n";
+    let file = TempFile::new("comments.txt", Some(content));
+    let mut todo_list = load(&file);
+
+    assert_eq!(todo_list.view_order(), vec![1, 4]);
+
+    todo_list.save(file.path()).unwrap();
+    assert_eq!(file.content(), content);
+}
+
+#[test]
+fn headers_stay_in_place_above_their_tasks() {
+    let content = "--- Groceries ---\n[X] milk\n[ ] bread\n\n--- Work ---\n[+] report\n";
+    let file = TempFile::new("headers.txt", Some(content));
+    let mut todo_list = load(&file);
+
+    // sorted by status within each section, the headers are never moved
+    assert_eq!(todo_list.view_order(), vec![0, 2, 1, 4, 5]);
+    assert!(todo_list.tasks[0].is_header());
+    assert_eq!(todo_list.tasks[0].task_type, TaskType::NotDefined);
+
+    todo_list.set_type(2, TaskType::Doing);
+    todo_list.save(file.path()).unwrap();
+    assert_eq!(
+        file.content(),
+        "--- Groceries ---\n[X] milk\n[+] bread\n\n--- Work ---\n[+] report\n"
+    );
+}
+
+#[test]
+fn a_color_override_survives_saving_in_every_format() {
+    for format in [FileFormat::Text, FileFormat::Json, FileFormat::Tsv] {
+        let file = TempFile::new("color.txt", Some("[ ] call mom\n"));
+        let mut todo_list = load(&file);
+        todo_list.format = format;
+        todo_list.set_color_override(0, Some(ConsoleForegroundColors::Red));
+        todo_list.save(file.path()).unwrap();
+
+        let mut reloaded = TodoList::new();
+        reloaded.format = format;
+        reloaded.load(file.path()).unwrap();
+        assert_eq!(
+            reloaded.tasks[0].color_override,
+            Some(ConsoleForegroundColors::Red)
+        );
+        assert_eq!(reloaded.tasks[0].task_type, TaskType::Todo);
+    }
+}
+
+#[test]
+fn changing_the_status_rewrites_the_prefix() {
+    let file = TempFile::new("change_type.txt", Some("[ ] first\n[+] second\n"));
+    let mut todo_list = load(&file);
+
+    todo_list.change_type(0);
+    todo_list.set_type(1, TaskType::Rejected);
+    todo_list.save(file.path()).unwrap();
+
+    assert_eq!(file.content(), "[+] first\n[-] second\n");
+}
+
+#[test]
+fn deleting_and_undoing_round_trips() {
+    let content = "[ ] first\n[ ] second\n[ ] third\n";
+    let file = TempFile::new("delete.txt", Some(content));
+    let mut todo_list = load(&file);
+
+    todo_list.delete_task(1);
+    todo_list.save(file.path()).unwrap();
+    assert_eq!(file.content(), "[ ] first\n[ ] third\n");
+
+    todo_list.undo();
+    todo_list.save(file.path()).unwrap();
+    assert_eq!(file.content(), content);
+}
+
+#[test]
+fn added_tasks_are_appended() {
+    let file = TempFile::new("add.txt", Some("[ ] first\n"));
+    let mut todo_list = load(&file);
+
+    todo_list.add("[ ] second", TaskType::Todo);
+    todo_list.save(file.path()).unwrap();
+
+    let content = file.content();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], "[ ] first");
+    assert!(lines[1].starts_with("[ ] second created:"));
+}
+
+#[test]
+fn json_files_round_trip() {
+    let file = TempFile::new("list.json", None);
+    let mut todo_list = load(&file);
+    todo_list
+        .tasks
+        .push(todo::Task::new("[+] \"quoted\" task", TaskType::Doing));
+    todo_list.save(file.path()).unwrap();
+
+    let reloaded = load(&file);
+    assert_eq!(reloaded.tasks.len(), 1);
+    assert_eq!(reloaded.tasks[0].task_type, TaskType::Doing);
+    assert_eq!(reloaded.tasks[0].text, "[+] \"quoted\" task");
+}
+
+#[test]
+fn saving_keeps_the_file_order_whatever_the_sort_mode() {
+    let content = "[X] done\n[ ] todo\n[-] rejected\n[+] doing\n";
+    let file = TempFile::new("order.txt", Some(content));
+    let mut todo_list = load(&file);
+
+    for sort_mode in [
+        SortMode::Status,
+        SortMode::ActiveFirst,
+        SortMode::Alphabetical,
+        SortMode::Insertion,
+    ] {
+        todo_list.sort_mode = sort_mode;
+        todo_list.save(file.path()).unwrap();
+        assert_eq!(file.content(), content);
+    }
+    assert_eq!(todo_list.view_order(), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn indented_lines_below_a_task_are_its_notes() {
+    let content =
+        "[ ] first\n  call Anna\n  \tbring the contract\n[ ] second\n  [ ] subtask\n    its note\n";
+    let file = TempFile::new("notes.txt", Some(content));
+    let mut todo_list = load(&file);
+
+    assert_eq!(todo_list.tasks.len(), 3);
+    assert_eq!(todo_list.tasks[0].notes, "call Anna\nbring the contract");
+    assert_eq!(todo_list.tasks[1].notes, "");
+    assert_eq!(todo_list.tasks[2].notes, "its note");
+
+    todo_list.save(file.path()).unwrap();
+    assert_eq!(
+        file.content(),
+        "[ ] first\n  call Anna\n  bring the contract\n[ ] second\n  [ ] subtask\n    its note\n"
+    );
+}
+
+#[test]
+fn indented_text_without_a_task_above_stays_a_line_of_its_own() {
+    let content = "  stray text\n# heading\n  # indented comment\n";
+    let file = TempFile::new("stray_notes.txt", Some(content));
+    let mut todo_list = load(&file);
+
+    assert_eq!(todo_list.tasks.len(), 3);
+    assert!(todo_list.tasks.iter().all(|task| task.notes.is_empty()));
+
+    todo_list.save(file.path()).unwrap();
+    assert_eq!(file.content(), content);
+}
+
+#[test]
+fn notes_survive_a_json_round_trip() {
+    let file = TempFile::new("notes.json", None);
+    let mut todo_list = load(&file);
+    todo_list
+        .tasks
+        .push(todo::Task::new("[ ] task", TaskType::Todo));
+    todo_list.set_notes(0, "line one\nline two");
+    todo_list.save(file.path()).unwrap();
+
+    let reloaded = load(&file);
+    assert_eq!(reloaded.tasks[0].notes, "line one\nline two");
+}
+
+#[test]
+fn notes_that_look_like_comments_or_tasks_survive_a_round_trip() {
+    let notes =
+        "# not a comment\n[ ] not a subtask\n[X] nor this\n\\ a backslash\ndone\tnot a tsv task";
+    for format in [FileFormat::Text, FileFormat::Tsv] {
+        let file = TempFile::new("escaped_notes.txt", None);
+        let mut todo_list = TodoList::new();
+        todo_list.format = format;
+        todo_list.load(file.path()).unwrap();
+        todo_list
+            .tasks
+            .push(todo::Task::new("[ ] task", TaskType::Todo));
+        todo_list.set_notes(0, notes);
+        todo_list.save(file.path()).unwrap();
+
+        let mut reloaded = TodoList::new();
+        reloaded.format = format;
+        reloaded.load(file.path()).unwrap();
+        assert_eq!(reloaded.tasks.len(), 1);
+        assert_eq!(reloaded.tasks[0].notes, notes);
+    }
+}
+
+#[test]
+fn invalid_utf8_lines_are_kept_with_replacement_characters() {
+    let file = TempFile::new("invalid_utf8.txt", None);
+    fs::write(&file.path, b"[ ] caf\xe9\n[+] second\n\xff\xfe\n[X] last\n").unwrap();
+    let mut todo_list = load(&file);
+
+    assert_eq!(todo_list.tasks.len(), 4);
+    assert_eq!(todo_list.tasks[0].text, "[ ] caf\u{fffd}");
+    assert_eq!(todo_list.tasks[1].task_type, TaskType::Doing);
+    assert_eq!(todo_list.tasks[2].text, "\u{fffd}\u{fffd}");
+    assert_eq!(todo_list.tasks[3].task_type, TaskType::Done);
+
+    todo_list.save(file.path()).unwrap();
+    assert_eq!(
+        file.content(),
+        "[ ] caf\u{fffd}\n[+] second\n\u{fffd}\u{fffd}\n[X] last\n"
+    );
+}
+
+#[test]
+fn every_status_change_appends_one_log_line() {
+    let file = TempFile::new("logged.txt", Some("[ ] write report\n[ ] outline\n"));
+    let log = TempFile::new("logged.txt.log", None);
+    let mut todo_list = load(&file);
+    todo_list.log_path = Some(log.path.clone());
+
+    todo_list.change_type(0);
+    todo_list.set_type(0, TaskType::Done);
+    // setting the status it already has is not a change
+    todo_list.set_type(1, TaskType::Todo);
+
+    let content = log.content();
+    let lines: Vec<Vec<&str>> = content
+        .lines()
+        .map(|line| line.split('\t').collect())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0][1..], ["Todo", "Doing", "write report"]);
+    assert_eq!(lines[1][1..], ["Doing", "Done", "write report"]);
+    assert!(lines[0][0].ends_with('Z'));
+}
+
+#[test]
+fn the_log_is_appended_to_and_never_truncated() {
+    let file = TempFile::new("appended.txt", Some("[ ] task\n"));
+    let log = TempFile::new("appended.txt.log", Some("earlier line\n"));
+    let mut todo_list = load(&file);
+    todo_list.log_path = Some(log.path.clone());
+
+    todo_list.change_type(0);
+    todo_list.save(file.path()).unwrap();
+
+    let content = log.content();
+    assert!(content.starts_with("earlier line\n"));
+    assert_eq!(content.lines().count(), 2);
+}
+
+#[test]
+fn tasks_with_the_same_status_keep_their_order_across_save_and_load() {
+    let file = TempFile::new(
+        "same_status.txt",
+        Some("[ ] c\n[X] b\n[ ] a\n[X] d\n[ ] b\n"),
+    );
+    let mut todo_list = load(&file);
+    // moved by hand so the order is not the alphabetical one
+    todo_list.move_to_top(4);
+    todo_list.save(file.path()).unwrap();
+
+    let reloaded = load(&file);
+    let shown: Vec<&str> = reloaded
+        .view_order()
+        .into_iter()
+        .map(|i| reloaded.tasks[i].text.as_str())
+        .collect();
+    assert_eq!(shown, ["[ ] b", "[ ] c", "[ ] a", "[X] b", "[X] d"]);
+}
+
+#[test]
+fn tsv_files_keep_their_keywords_subtasks_and_notes() {
+    let content = "# plan\ntodo\twrite report\n  doing\t! outline\n    the intro first\ndone\tship\nrejected\tskip\n";
+    let file = TempFile::new("list.tsv", Some(content));
+    let mut todo_list = TodoList::new();
+    todo_list.format = FileFormat::Tsv;
+    todo_list.load(file.path()).unwrap();
+
+    let types: Vec<TaskType> = todo_list.tasks.iter().map(|task| task.task_type).collect();
+    assert_eq!(
+        types,
+        [
+            TaskType::NotDefined,
+            TaskType::Todo,
+            TaskType::Doing,
+            TaskType::Done,
+            TaskType::Rejected
+        ]
+    );
+    assert_eq!(todo_list.tasks[2].text, "[+] ! outline");
+    assert_eq!(todo_list.tasks[2].depth, 1);
+    assert_eq!(todo_list.tasks[2].notes, "the intro first");
+
+    todo_list.save(file.path()).unwrap();
+    assert_eq!(file.content(), content);
+}
+
+#[test]
+fn text_converts_to_tsv_and_back() {
+    let content = "[ ] write report\n  [+] outline\n[X] ship\n# done\n[-] skip\n";
+    let file = TempFile::new("convert.txt", Some(content));
+    let tsv = TempFile::new("convert.tsv", None);
+
+    let mut todo_list = load(&file);
+    todo_list.format = FileFormat::Tsv;
+    todo_list.save(tsv.path()).unwrap();
+    assert_eq!(
+        tsv.content(),
+        "todo\twrite report\n  doing\toutline\ndone\tship\n# done\nrejected\tskip\n"
+    );
+
+    let mut todo_list = TodoList::new();
+    todo_list.format = FileFormat::Tsv;
+    todo_list.load(tsv.path()).unwrap();
+    todo_list.format = FileFormat::Text;
+    todo_list.save(file.path()).unwrap();
+    assert_eq!(file.content(), content);
+}
+
+#[test]
+fn the_picker_lists_only_the_archived_tasks_and_restores_the_picked_one() {
+    let archive = TempFile::new(
+        "picker.archive.txt",
+        Some(concat!(
+            "# archived\n",
+            "[X] first archived:2026-01-01\n",
+            "\n",
+            "[-] second archived:2026-01-02\n",
+            "    a note\n",
+            "[X] third archived:2026-01-03\n",
+        )),
+    );
+    let archive_path = Path::new(archive.path());
+
+    let archived = archived_tasks(archive_path).unwrap();
+    let texts: Vec<&str> = archived.iter().map(|task| task.text.as_str()).collect();
+    assert_eq!(
+        texts,
+        [
+            "[X] first archived:2026-01-01",
+            "[-] second archived:2026-01-02",
+            "[X] third archived:2026-01-03",
+        ]
+    );
+
+    let mut todo_list = TodoList::new();
+    assert_eq!(todo_list.unarchive(archive_path, 1).unwrap(), Some(0));
+    assert_eq!(todo_list.tasks[0].text, "[-] second");
+    assert_eq!(todo_list.tasks[0].task_type, TaskType::Rejected);
+    assert_eq!(todo_list.tasks[0].notes, "a note");
+    assert_eq!(
+        archive.content(),
+        "# archived\n[X] first archived:2026-01-01\n\n[X] third archived:2026-01-03\n"
+    );
+}
+
+#[test]
+fn an_archived_task_comes_back_to_the_end_of_the_list() {
+    let file = TempFile::new("restored.txt", Some("[ ] open\n[X] done\n"));
+    let archive = TempFile::new("restored.archive.txt", None);
+    let archive_path = Path::new(archive.path());
+    let mut todo_list = load(&file);
+    todo_list.archive_completed(archive_path).unwrap();
+
+    assert_eq!(todo_list.unarchive(archive_path, 1).unwrap(), None);
+    assert_eq!(todo_list.unarchive(archive_path, 0).unwrap(), Some(1));
+    todo_list.save(file.path()).unwrap();
+
+    assert_eq!(file.content(), "[ ] open\n[X] done\n");
+    assert_eq!(archive.content(), "");
+    assert!(archived_tasks(archive_path).unwrap().is_empty());
+}
+
+#[test]
+fn the_json_export_has_one_object_per_task_and_reads_back() {
+    let tasks = vec![
+        todo::Task::new("[ ] say \"hi\"", TaskType::Todo),
+        todo::Task::new("[X] back\\slash", TaskType::Done),
+    ];
+
+    let json = tasks_to_json(&tasks);
+
+    assert_eq!(
+        json,
+        concat!(
+            "[\n",
+            "  {\"task_type\": \"Todo\", \"text\": \"[ ] say \\\"hi\\\"\", \"notes\": \"\"},\n",
+            "  {\"task_type\": \"Done\", \"text\": \"[X] back\\\\slash\", \"notes\": \"\"}\n",
+            "]\n",
+        )
+    );
+    let read = tasks_from_json(&json).unwrap();
+    assert_eq!(read.len(), 2);
+    assert_eq!(read[0].text, "[ ] say \"hi\"");
+    assert_eq!(read[1].task_type, TaskType::Done);
+    assert_eq!(read[1].text, "[X] back\\slash");
+}
+
+fn tasks(lines: &[&str]) -> TodoList {
+    let mut todo_list = TodoList::new();
+    for line in lines {
+        todo_list
+            .tasks
+            .push(todo::Task::new(line, todo::get_type_from_string(line)));
+    }
+    todo_list
+}
+
+#[test]
+fn markdown_export_checks_only_the_done_tasks() {
+    let todo_list = tasks(&[
+        "# groceries",
+        "[ ] milk",
+        "[+] bread",
+        "[X] eggs",
+        "[-] ham",
+    ]);
+
+    assert_eq!(
+        to_markdown(&todo_list),
+        "# groceries\n- [ ] milk\n- [ ] bread (doing)\n- [x] eggs\n- [ ] ham (rejected)\n"
+    );
+}
+
+#[test]
+fn markdown_export_nests_subtasks_and_drops_the_markers_and_stamps() {
+    let todo_list = tasks(&[
+        "[+] !! plan trip created:2024-01-01 color:red",
+        "  [X] ~ book hotel due:2024-02-01 created:2024-01-02 completed:2024-01-05",
+        "    [ ] pay deposit",
+    ]);
+
+    assert_eq!(
+        to_markdown(&todo_list),
+        "- [ ] plan trip (doing)\n  - [x] book hotel due:2024-02-01\n    - [ ] pay deposit\n"
+    );
+}
+
+#[test]
+fn markdown_import_reads_the_checkboxes_and_the_export_suffixes() {
+    let markdown = "# notes\n- [ ] milk\n* [x] eggs\n  + [X] ham\n- [ ] bread (doing)\n\
+                    - [ ] fish (rejected)\n- plain item\n- [ ]   \n";
+
+    let imported = from_markdown(markdown);
+
+    let lines: Vec<(&str, TaskType)> = imported
+        .iter()
+        .map(|task| (task.text.as_str(), task.task_type))
+        .collect();
+    assert_eq!(
+        lines,
+        [
+            ("[ ] milk", TaskType::Todo),
+            ("[X] eggs", TaskType::Done),
+            ("[X] ham", TaskType::Done),
+            ("[+] bread", TaskType::Doing),
+            ("[-] fish", TaskType::Rejected),
+        ]
+    );
+    assert!(imported
+        .iter()
+        .all(|task| task.priority == Priority::default()));
+}
+
+#[test]
+fn markdown_export_reads_back_the_same_tasks() {
+    let todo_list = tasks(&["[ ] milk", "[+] bread", "[X] eggs", "[-] ham"]);
+
+    let imported = from_markdown(&to_markdown(&todo_list));
+
+    let texts: Vec<&str> = imported.iter().map(|task| task.text.as_str()).collect();
+    assert_eq!(texts, ["[ ] milk", "[+] bread", "[X] eggs", "[-] ham"]);
+}
+
+#[test]
+fn open_subtasks_of_an_archived_task_stay_a_level_up() {
+    let file = TempFile::new(
+        "orphans.txt",
+        Some("[X] done\n  [ ] open\n    [ ] deeper\n  [X] closed\n[ ] next\n"),
+    );
+    let archive = TempFile::new("orphans.archive.txt", None);
+    let mut todo_list = load(&file);
+
+    assert_eq!(
+        todo_list
+            .archive_completed(Path::new(archive.path()))
+            .unwrap(),
+        2
+    );
+    todo_list.save(file.path()).unwrap();
+    assert_eq!(file.content(), "[ ] open\n  [ ] deeper\n[ ] next\n");
+}
+
+#[test]
+fn windows_line_endings_are_read_without_carriage_returns_and_saved_as_newlines() {
+    let file = TempFile::new(
+        "crlf.txt",
+        Some("[ ] first  \r\n    a note\r\n[X] second\r\n\r\n# end\r\n"),
+    );
+    let mut todo_list = load(&file);
+
+    assert!(todo_list
+        .tasks
+        .iter()
+        .all(|task| !task.text.contains('\r') && !task.notes.contains('\r')));
+    assert_eq!(todo_list.tasks[0].text, "[ ] first");
+    assert_eq!(todo_list.tasks[0].notes, "a note");
+    assert_eq!(todo_list.tasks[1].task_type, TaskType::Done);
+
+    todo_list.save(file.path()).unwrap();
+    assert_eq!(file.content(), "[ ] first\n  a note\n[X] second\n\n# end\n");
+}
+
+#[test]
+fn archiving_prunes_the_finished_tasks_and_appends_to_the_archive() {
+    let file = TempFile::new(
+        "archiving.txt",
+        Some("[X] paid\n[ ] open\n[-] dropped\n    why\n[+] started\n"),
+    );
+    let archive = TempFile::new(
+        "archiving.archive.txt",
+        Some("[X] older archived:2020-01-01\n"),
+    );
+    let archive_path = Path::new(archive.path());
+    let today = Date::today();
+    let mut todo_list = load(&file);
+
+    assert_eq!(todo_list.archive_completed(archive_path).unwrap(), 2);
+    assert_eq!(todo_list.tasks.len(), 2);
+    assert!(todo_list.is_dirty);
+    todo_list.save(file.path()).unwrap();
+    assert_eq!(file.content(), "[ ] open\n[+] started\n");
+    assert_eq!(
+        archive.content(),
+        format!(
+            "[X] older archived:2020-01-01\n[X] paid archived:{today}\n[-] dropped archived:{today}\n  why\n"
+        )
+    );
+
+    // a later session adds below what is already there
+    assert_eq!(todo_list.archive_completed(archive_path).unwrap(), 0);
+    todo_list.set_type(1, TaskType::Done);
+    assert_eq!(todo_list.archive_completed(archive_path).unwrap(), 1);
+    assert_eq!(archived_tasks(archive_path).unwrap().len(), 4);
+    assert!(archive
+        .content()
+        .starts_with("[X] older archived:2020-01-01\n[X] paid"));
+    assert_eq!(todo_list.undo(), None);
+}
+
+#[test]
+fn json_escapes_outside_the_basic_plane_are_read_as_one_character() {
+    let content = concat!(
+        "[{\"task_type\": \"Done\", \"text\": \"[X] party \\ud83d\\ude00\", ",
+        "\"notes\": \"half \\ud83d pair\", \"pinned\": true, \"order\": 1.5, \"tag\": null}]"
+    );
+
+    let tasks = tasks_from_json(content).unwrap();
+
+    assert_eq!(tasks[0].text, "[X] party 😀");
+    assert_eq!(tasks[0].notes, "half \u{fffd} pair");
+    assert_eq!(tasks[0].task_type, TaskType::Done);
+    assert_eq!(
+        tasks_from_json(&tasks_to_json(&tasks)).unwrap()[0].text,
+        "[X] party 😀"
+    );
+}
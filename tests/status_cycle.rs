@@ -0,0 +1,33 @@
+use todo::task::{parse_status_cycle, set_status_cycle};
+use todo::{TaskType, TodoList};
+
+// the cycle is set once per process, so this is the only test in this file
+#[test]
+fn a_custom_cycle_without_rejected_goes_from_done_back_to_todo() {
+    set_status_cycle(parse_status_cycle("todo,doing,done").unwrap());
+
+    assert_eq!(TaskType::Todo.next(), TaskType::Doing);
+    assert_eq!(TaskType::Doing.next(), TaskType::Done);
+    assert_eq!(TaskType::Done.next(), TaskType::Todo);
+    assert_eq!(TaskType::Todo.prev(), TaskType::Done);
+    // a status left out of the cycle joins it at the start
+    assert_eq!(TaskType::Rejected.next(), TaskType::Todo);
+    assert_eq!(TaskType::NotDefined.next(), TaskType::NotDefined);
+
+    let mut todo_list = TodoList::new();
+    todo_list.add("[ ] chore", TaskType::Todo);
+    let mut seen = Vec::new();
+    for _ in 0..4 {
+        todo_list.change_type(0);
+        seen.push(todo_list.tasks[0].task_type);
+    }
+    assert_eq!(
+        seen,
+        [
+            TaskType::Doing,
+            TaskType::Done,
+            TaskType::Todo,
+            TaskType::Doing
+        ]
+    );
+}
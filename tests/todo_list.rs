@@ -0,0 +1,1041 @@
+use todo::console::Console;
+use todo::date::Date;
+use todo::task::{compose_task_text, get_description, get_priority_from_string, Priority};
+use todo::todo_list::{Direction, SortMode};
+use todo::{get_type_from_string, Task, TaskType, TodoList};
+
+fn list(lines: &[&str]) -> TodoList {
+    let mut todo_list = TodoList::new();
+    for line in lines {
+        todo_list
+            .tasks
+            .push(Task::new(line, get_type_from_string(line)));
+    }
+    todo_list
+}
+
+fn texts(todo_list: &TodoList) -> Vec<&str> {
+    todo_list
+        .tasks
+        .iter()
+        .map(|task| task.text.as_str())
+        .collect()
+}
+
+fn lines(todo_list: &TodoList) -> Vec<String> {
+    todo_list.tasks.iter().map(|task| task.line()).collect()
+}
+
+#[test]
+fn move_to_top_keeps_the_order_of_the_others() {
+    let mut todo_list = list(&["[ ] a", "[ ] b", "[ ] c", "[ ] d"]);
+
+    assert_eq!(todo_list.move_to_top(2), Some(0));
+    assert_eq!(texts(&todo_list), ["[ ] c", "[ ] a", "[ ] b", "[ ] d"]);
+    assert!(todo_list.is_dirty);
+
+    // a parent goes up with its subtasks, which keep their own order
+    let mut todo_list = list(&["[ ] a", "[ ] b", "  [ ] b1", "  [ ] b2", "[ ] c"]);
+    assert_eq!(todo_list.move_to_top(1), Some(0));
+    assert_eq!(
+        lines(&todo_list),
+        ["[ ] b", "  [ ] b1", "  [ ] b2", "[ ] a", "[ ] c"]
+    );
+}
+
+#[test]
+fn move_to_bottom_keeps_the_order_of_the_others() {
+    let mut todo_list = list(&["[ ] a", "[ ] b", "[ ] c", "[ ] d"]);
+
+    assert_eq!(todo_list.move_to_bottom(1), Some(3));
+    assert_eq!(texts(&todo_list), ["[ ] a", "[ ] c", "[ ] d", "[ ] b"]);
+
+    // the parent lands below the subtasks of the last task, not between them
+    let mut todo_list = list(&["[ ] a", "  [ ] a1", "[ ] b", "[ ] c", "  [ ] c1"]);
+    assert_eq!(todo_list.move_to_bottom(0), Some(3));
+    assert_eq!(
+        lines(&todo_list),
+        ["[ ] b", "[ ] c", "  [ ] c1", "[ ] a", "  [ ] a1"]
+    );
+}
+
+#[test]
+fn moving_stays_inside_the_leading_and_trailing_comments() {
+    let mut todo_list = list(&["# heading", "[ ] a", "[ ] b", "", "# footer"]);
+
+    assert_eq!(todo_list.move_to_top(2), Some(1));
+    assert_eq!(
+        texts(&todo_list),
+        ["# heading", "[ ] b", "[ ] a", "", "# footer"]
+    );
+    assert_eq!(todo_list.move_to_bottom(1), Some(2));
+    assert_eq!(
+        texts(&todo_list),
+        ["# heading", "[ ] a", "[ ] b", "", "# footer"]
+    );
+}
+
+#[test]
+fn moving_a_task_already_at_the_edge_does_nothing() {
+    let mut todo_list = list(&["[ ] a", "[ ] b"]);
+
+    assert_eq!(todo_list.move_to_top(0), None);
+    assert_eq!(todo_list.move_to_bottom(1), None);
+    assert!(!todo_list.is_dirty);
+}
+
+#[test]
+fn undoing_a_move_restores_the_order() {
+    let mut todo_list = list(&["[ ] a", "[ ] b", "[ ] c"]);
+
+    todo_list.move_to_bottom(0);
+    assert_eq!(todo_list.undo(), Some(0));
+    assert_eq!(texts(&todo_list), ["[ ] a", "[ ] b", "[ ] c"]);
+    assert_eq!(todo_list.redo(), Some(2));
+    assert_eq!(texts(&todo_list), ["[ ] b", "[ ] c", "[ ] a"]);
+}
+
+fn visible(todo_list: &TodoList) -> Vec<&str> {
+    todo_list
+        .view_order()
+        .into_iter()
+        .map(|i| todo_list.tasks[i].text.as_str())
+        .collect()
+}
+
+#[test]
+fn hiding_a_status_leaves_the_others_visible() {
+    let mut todo_list = list(&["[ ] a", "[+] b", "[X] c", "[-] d"]);
+    todo_list.sort_mode = SortMode::Insertion;
+
+    assert!(!todo_list.toggle_type_visibility(TaskType::Done));
+    assert_eq!(visible(&todo_list), ["[ ] a", "[+] b", "[-] d"]);
+}
+
+#[test]
+fn hiding_several_statuses_combines_them() {
+    let mut todo_list = list(&["[ ] a", "[+] b", "[X] c", "[-] d", "[X] e"]);
+    todo_list.sort_mode = SortMode::Insertion;
+
+    todo_list.toggle_type_visibility(TaskType::Done);
+    todo_list.toggle_type_visibility(TaskType::Rejected);
+    assert_eq!(visible(&todo_list), ["[ ] a", "[+] b"]);
+
+    todo_list.toggle_type_visibility(TaskType::Todo);
+    todo_list.toggle_type_visibility(TaskType::Doing);
+    assert!(visible(&todo_list).is_empty());
+}
+
+#[test]
+fn toggling_a_status_twice_shows_it_again() {
+    let mut todo_list = list(&["[ ] a", "[X] b"]);
+    todo_list.sort_mode = SortMode::Insertion;
+
+    todo_list.toggle_type_visibility(TaskType::Todo);
+    assert!(todo_list.toggle_type_visibility(TaskType::Todo));
+    assert!(todo_list.hidden_types.is_empty());
+    assert_eq!(visible(&todo_list), ["[ ] a", "[X] b"]);
+}
+
+#[test]
+fn hidden_statuses_combine_with_the_search() {
+    let mut todo_list = list(&["[ ] buy milk", "[X] buy bread", "[ ] call mom"]);
+    todo_list.sort_mode = SortMode::Insertion;
+    todo_list.filter = "buy".to_string();
+
+    todo_list.toggle_type_visibility(TaskType::Done);
+    assert_eq!(visible(&todo_list), ["[ ] buy milk"]);
+    assert_eq!(todo_list.task_index(1), Some(0));
+    assert_eq!(todo_list.task_index(2), None);
+}
+
+#[test]
+fn insert_puts_the_task_at_the_index() {
+    let mut todo_list = list(&["[ ] a", "[ ] b", "[ ] c"]);
+
+    todo_list.insert(1, "[ ] new", TaskType::Todo);
+    assert_eq!(todo_list.tasks.len(), 4);
+    assert!(todo_list.tasks[1].text.starts_with("[ ] new created:"));
+    assert_eq!(todo_list.tasks[2].text, "[ ] b");
+    assert!(todo_list.is_dirty);
+}
+
+#[test]
+fn insert_above_the_first_task() {
+    let mut todo_list = list(&["[ ] a", "[ ] b"]);
+
+    todo_list.insert(0, "[ ] new", TaskType::Todo);
+    assert!(todo_list.tasks[0].text.starts_with("[ ] new"));
+    assert_eq!(todo_list.tasks[1].text, "[ ] a");
+}
+
+#[test]
+fn insert_below_the_last_task() {
+    let mut todo_list = list(&["[ ] a", "[ ] b"]);
+
+    todo_list.insert(2, "[ ] new", TaskType::Todo);
+    assert_eq!(todo_list.tasks[1].text, "[ ] b");
+    assert!(todo_list.tasks[2].text.starts_with("[ ] new"));
+}
+
+#[test]
+fn insert_keeps_the_indentation_as_depth() {
+    let mut todo_list = list(&["[ ] a", "  [ ] b"]);
+
+    todo_list.insert(2, "  [ ] new", TaskType::Todo);
+    assert_eq!(todo_list.tasks[2].depth, 1);
+    assert_eq!(todo_list.children(0), [1, 2]);
+}
+
+#[test]
+fn undoing_an_insert_removes_the_task_again() {
+    let mut todo_list = list(&["[ ] a", "[ ] b"]);
+
+    todo_list.insert(1, "[ ] new", TaskType::Todo);
+    todo_list.undo();
+    assert_eq!(texts(&todo_list), ["[ ] a", "[ ] b"]);
+}
+
+#[test]
+fn set_types_changes_the_tasks_in_one_undo_step() {
+    let mut todo_list = list(&["[ ] a", "[+] b", "[X] c", "[ ] d"]);
+
+    assert_eq!(todo_list.set_types(&[0, 1, 2], TaskType::Done), 2);
+    assert_eq!(todo_list.tasks[0].task_type, TaskType::Done);
+    assert_eq!(todo_list.tasks[1].task_type, TaskType::Done);
+    assert_eq!(todo_list.tasks[3].task_type, TaskType::Todo);
+
+    todo_list.undo();
+    assert_eq!(todo_list.tasks[0].task_type, TaskType::Todo);
+    assert_eq!(todo_list.tasks[1].task_type, TaskType::Doing);
+}
+
+#[test]
+fn a_selection_covers_the_rows_in_either_direction() {
+    let mut console = Console::new();
+    console.cursor_position.1 = 2;
+    assert_eq!(console.selection(), None);
+    assert!(console.is_highlighted(2));
+    assert!(!console.is_highlighted(3));
+
+    console.selection_anchor = Some(2);
+    console.cursor_position.1 = 4;
+    assert_eq!(console.selection(), Some((2, 4)));
+
+    console.cursor_position.1 = 1;
+    assert_eq!(console.selection(), Some((1, 2)));
+    assert!(console.is_highlighted(1) && console.is_highlighted(2));
+    assert!(!console.is_highlighted(3));
+}
+
+#[test]
+fn dedupe_keeps_the_most_advanced_status() {
+    let mut todo_list = list(&[
+        "[ ] buy milk",
+        "[X] buy milk",
+        "[+] buy milk",
+        "[ ] call mom",
+    ]);
+
+    assert_eq!(todo_list.duplicates(), [0, 2]);
+    assert_eq!(todo_list.dedupe(), 2);
+    assert_eq!(texts(&todo_list), ["[X] buy milk", "[ ] call mom"]);
+}
+
+#[test]
+fn dedupe_ignores_the_priority_and_dates() {
+    let mut todo_list = list(&[
+        "[+] ! write report created:2024-01-02",
+        "[ ] write report created:2024-03-04",
+    ]);
+
+    assert_eq!(todo_list.dedupe(), 1);
+    assert_eq!(texts(&todo_list), ["[+] ! write report created:2024-01-02"]);
+}
+
+#[test]
+fn dedupe_keeps_the_first_of_equal_statuses() {
+    let mut todo_list = list(&["[X] a", "[ ] b", "[X] a"]);
+
+    assert_eq!(todo_list.dedupe(), 1);
+    assert_eq!(texts(&todo_list), ["[X] a", "[ ] b"]);
+}
+
+#[test]
+fn dedupe_leaves_subtasks_of_other_parents_and_comments_alone() {
+    let mut todo_list = list(&[
+        "# notes",
+        "[ ] a",
+        "  [ ] step",
+        "[ ] b",
+        "  [ ] step",
+        "# notes",
+    ]);
+
+    assert_eq!(todo_list.dedupe(), 0);
+    assert_eq!(todo_list.tasks.len(), 6);
+}
+
+#[test]
+fn undoing_a_dedupe_restores_every_task() {
+    let mut todo_list = list(&["[ ] a", "[ ] b", "[+] a", "[X] b"]);
+
+    assert_eq!(todo_list.dedupe(), 2);
+    todo_list.undo();
+    assert_eq!(texts(&todo_list), ["[ ] a", "[ ] b", "[+] a", "[X] b"]);
+}
+
+#[test]
+fn focus_shows_only_the_doing_tasks_and_restores_the_view() {
+    let mut todo_list = list(&["[ ] a", "[+] b", "[X] c", "[-] d", "[+] e"]);
+    todo_list.sort_mode = SortMode::Insertion;
+    todo_list.toggle_type_visibility(TaskType::Rejected);
+
+    assert!(todo_list.toggle_focus());
+    assert!(todo_list.is_focused());
+    assert_eq!(visible(&todo_list), ["[+] b", "[+] e"]);
+
+    assert!(!todo_list.toggle_focus());
+    assert!(!todo_list.is_focused());
+    assert_eq!(todo_list.hidden_types, [TaskType::Rejected]);
+    assert_eq!(visible(&todo_list), ["[ ] a", "[+] b", "[X] c", "[+] e"]);
+}
+
+#[test]
+fn split_task_puts_the_second_half_below_with_the_same_status() {
+    let mut todo_list = list(&["[+] ~ write report and send it", "[ ] other"]);
+
+    assert_eq!(todo_list.split_task(0, 12), Some(1));
+    assert_eq!(
+        texts(&todo_list),
+        ["[+] ~ write report", "[+] ~ and send it", "[ ] other"]
+    );
+    assert_eq!(todo_list.tasks[1].task_type, TaskType::Doing);
+}
+
+#[test]
+fn split_task_keeps_the_dates_on_the_first_half_and_stamps_the_second_today() {
+    let mut todo_list = list(&[
+        "[X] write report due:2026-02-01 and send it created:2026-01-01 completed:2026-01-20",
+    ]);
+    let today = Date::today();
+
+    assert_eq!(todo_list.split_task(0, 13), Some(1));
+    assert_eq!(
+        texts(&todo_list),
+        [
+            "[X] write report due:2026-02-01 created:2026-01-01 completed:2026-01-20".to_string(),
+            format!("[X] and send it created:{} completed:{}", today, today),
+        ]
+    );
+    assert_eq!(todo_list.tasks[0].due, Date::parse("2026-02-01"));
+    assert_eq!(todo_list.tasks[0].created_at, Date::parse("2026-01-01"));
+    assert_eq!(todo_list.tasks[1].due, None);
+    assert_eq!(todo_list.tasks[1].created_at, Some(today));
+    assert_eq!(todo_list.tasks[1].completed_at, Some(today));
+
+    // the dates alone are not a half of their own, even when split inside one
+    let mut todo_list = list(&["[ ] write report created:2026-01-01"]);
+    assert_eq!(todo_list.split_task(0, 13), None);
+    assert_eq!(todo_list.split_task(0, 20), None);
+}
+
+#[test]
+fn split_task_at_the_start_or_the_end_does_nothing() {
+    let mut todo_list = list(&["[ ] write report"]);
+
+    assert_eq!(todo_list.split_task(0, 0), None);
+    assert_eq!(todo_list.split_task(0, 12), None);
+    assert_eq!(todo_list.split_task(0, 100), None);
+    // only spaces on one side is still an empty half
+    let mut spaced = list(&["[ ] write report   "]);
+    assert_eq!(spaced.split_task(0, 13), None);
+    assert_eq!(texts(&todo_list), ["[ ] write report"]);
+    assert!(!todo_list.is_dirty);
+}
+
+#[test]
+fn split_task_counts_characters_not_bytes() {
+    let mut todo_list = list(&["[ ] café crème"]);
+
+    assert_eq!(todo_list.split_task(0, 4), Some(1));
+    assert_eq!(texts(&todo_list), ["[ ] café", "[ ] crème"]);
+}
+
+#[test]
+fn split_task_keeps_the_subtasks_with_the_first_half() {
+    let mut todo_list = list(&["[ ] plan trip", "  [ ] book", "[ ] other"]);
+
+    assert_eq!(todo_list.split_task(0, 4), Some(2));
+    assert_eq!(
+        texts(&todo_list),
+        ["[ ] plan", "[ ] book", "[ ] trip", "[ ] other"]
+    );
+    assert_eq!(todo_list.tasks[2].depth, 0);
+
+    todo_list.undo();
+    assert_eq!(
+        texts(&todo_list),
+        ["[ ] plan trip", "[ ] book", "[ ] other"]
+    );
+}
+
+#[test]
+fn merge_with_next_joins_the_texts_and_keeps_the_status() {
+    let mut todo_list = list(&[
+        "[+] write report",
+        "# notes",
+        "[X] and send it",
+        "[ ] other",
+    ]);
+
+    assert_eq!(todo_list.merge_with_next(0), Some(0));
+    assert_eq!(
+        texts(&todo_list),
+        ["[+] write report and send it", "# notes", "[ ] other"]
+    );
+    assert_eq!(todo_list.tasks[0].task_type, TaskType::Doing);
+}
+
+#[test]
+fn merge_with_next_drops_the_dates_of_the_next_task() {
+    let mut todo_list = list(&[
+        "[ ] a created:2024-01-01",
+        "[X] b created:2024-02-02 completed:2024-02-03",
+    ]);
+
+    assert_eq!(todo_list.merge_with_next(0), Some(0));
+    assert_eq!(texts(&todo_list), ["[ ] a created:2024-01-01 b"]);
+}
+
+#[test]
+fn merge_with_next_on_the_last_task_does_nothing() {
+    let mut todo_list = list(&["[ ] a", "[ ] b", "# end"]);
+
+    assert_eq!(todo_list.merge_with_next(1), None);
+    assert_eq!(todo_list.tasks.len(), 3);
+    assert!(!todo_list.is_dirty);
+}
+
+#[test]
+fn merge_with_next_takes_the_task_on_the_next_row_and_keeps_one_due_date() {
+    let mut todo_list = list(&[
+        "[X] c due:2024-03-01",
+        "[ ] a due:2024-01-01",
+        "[ ] b due:2024-02-02",
+    ]);
+    todo_list.sort_mode = SortMode::Status;
+
+    assert_eq!(todo_list.merge_with_next(1), Some(1));
+    assert_eq!(
+        texts(&todo_list),
+        ["[X] c due:2024-03-01", "[ ] a due:2024-01-01 b"]
+    );
+    assert_eq!(todo_list.tasks[1].due, Date::parse("2024-01-01"));
+
+    // the next row of the last todo is the done task above it in the file
+    assert_eq!(todo_list.merge_with_next(1), Some(0));
+    assert_eq!(texts(&todo_list), ["[ ] a due:2024-01-01 b c"]);
+    assert_eq!(todo_list.undo(), Some(1));
+    assert_eq!(
+        texts(&todo_list),
+        ["[X] c due:2024-03-01", "[ ] a due:2024-01-01 b"]
+    );
+}
+
+#[test]
+fn merge_with_next_stops_at_headers_and_other_depths() {
+    let mut todo_list = list(&["[ ] a", "  [ ] a1", "[ ] b", "--- later ---", "[ ] c"]);
+
+    assert_eq!(todo_list.merge_with_next(0), None);
+    assert_eq!(todo_list.merge_with_next(1), None);
+    assert_eq!(todo_list.merge_with_next(2), None);
+    assert_eq!(todo_list.tasks.len(), 5);
+    assert!(!todo_list.is_dirty);
+}
+
+#[test]
+fn merging_a_task_with_subtasks_keeps_them_a_level_up() {
+    let mut todo_list = list(&["[ ] a", "[ ] b", "  [ ] b1"]);
+
+    assert_eq!(todo_list.merge_with_next(0), Some(0));
+    assert_eq!(texts(&todo_list), ["[ ] a b", "[ ] b1"]);
+    todo_list.undo();
+    assert_eq!(lines(&todo_list), ["[ ] a", "[ ] b", "  [ ] b1"]);
+}
+
+#[test]
+fn undoing_a_merge_brings_the_next_task_back() {
+    let mut todo_list = list(&["[ ] a", "[X] b"]);
+    todo_list.set_notes(1, "note of b");
+
+    assert_eq!(todo_list.merge_with_next(0), Some(0));
+    assert_eq!(todo_list.tasks[0].notes, "note of b");
+    todo_list.undo();
+    assert_eq!(texts(&todo_list), ["[ ] a", "[X] b"]);
+    assert_eq!(todo_list.tasks[0].notes, "");
+    assert_eq!(todo_list.tasks[1].notes, "note of b");
+}
+
+#[test]
+fn display_text_leaves_out_the_status_marker() {
+    let task = Task::new("[+] !! write report", TaskType::Doing);
+    assert_eq!(task.display_text(), "!! write report");
+
+    for short in ["", "[", "[ ", "ab", "[ ]"] {
+        let task = Task::new(short, get_type_from_string(short));
+        let expected = if short == "[ ]" { "" } else { short };
+        assert_eq!(task.display_text(), expected);
+    }
+}
+
+#[test]
+fn set_display_text_keeps_the_status_marker() {
+    let mut task = Task::new("[X] old #tag", TaskType::Done);
+
+    assert_eq!(task.set_display_text("new due:2024-05-01"), "[X] old #tag");
+    assert_eq!(task.text, "[X] new due:2024-05-01");
+    assert_eq!(task.task_type, TaskType::Done);
+    assert!(task.tags.is_empty());
+    assert!(task.due.is_some());
+
+    let mut task = Task::new("ab", TaskType::NotDefined);
+    task.set_display_text("c");
+    assert_eq!(task.text, "c");
+    assert_eq!(task.task_type, TaskType::NotDefined);
+}
+
+#[test]
+fn filtering_does_not_match_the_status_marker() {
+    let mut todo_list = list(&["[+] a", "[ ] b [+] c"]);
+    todo_list.filter = "[+]".to_string();
+
+    assert_eq!(todo_list.view_order(), [1]);
+}
+
+#[test]
+fn setting_the_status_of_a_line_without_a_marker_prepends_one() {
+    for (text, expected) in [
+        ("", "[+]"),
+        ("a", "[+] a"),
+        ("ab", "[+] ab"),
+        ("é", "[+] é"),
+    ] {
+        let mut task = Task::new(text, TaskType::NotDefined);
+        task.set_type(TaskType::Doing);
+        assert_eq!(task.text, expected);
+        assert_eq!(task.task_type, TaskType::Doing);
+    }
+
+    let mut task = Task::new("x", TaskType::NotDefined);
+    task.set_type(TaskType::Done);
+    assert!(task.text.starts_with("[X] x completed:"));
+}
+
+#[test]
+fn setting_the_status_of_a_plain_line_reads_its_markers_again() {
+    let mut task = Task::new("~ water plants due:2024-05-01", TaskType::NotDefined);
+    assert_eq!(task.priority, Priority::Medium);
+
+    task.set_type(TaskType::Todo);
+    assert_eq!(task.text, "[ ] ~ water plants due:2024-05-01");
+    assert_eq!(task.task_type, get_type_from_string(&task.text));
+    assert_eq!(task.priority, Priority::Low);
+    assert_eq!(task.due, Date::parse("2024-05-01"));
+}
+
+#[test]
+fn keywords_name_the_statuses_in_any_case() {
+    for task_type in [
+        TaskType::Todo,
+        TaskType::Doing,
+        TaskType::Done,
+        TaskType::Rejected,
+    ] {
+        let keyword = task_type.as_keyword();
+        assert_eq!(TaskType::from_keyword(keyword), Some(task_type));
+        assert_eq!(
+            TaskType::from_keyword(&keyword.to_uppercase()),
+            Some(task_type)
+        );
+    }
+    assert_eq!(TaskType::from_keyword(" Doing "), Some(TaskType::Doing));
+    assert_eq!(TaskType::from_keyword("finished"), None);
+    assert_eq!(TaskType::from_keyword(""), None);
+    assert_eq!(TaskType::from_keyword("[X]"), None);
+}
+
+#[test]
+fn the_today_view_has_the_doing_tasks_and_the_ones_due_today() {
+    let today = Date::today();
+    let tomorrow = Date::from_days(today.days_since_epoch() + 1);
+    let due_today = format!("[X] pay rent due:{}", today);
+    let due_tomorrow = format!("[ ] call mom due:{}", tomorrow);
+    let mut todo_list = list(&[
+        "[ ] someday",
+        "# comment",
+        "[+] write report",
+        due_today.as_str(),
+        due_tomorrow.as_str(),
+    ]);
+
+    // hidden statuses do not hide the tasks for today
+    todo_list.hidden_types.push(TaskType::Done);
+    assert!(todo_list.toggle_today());
+    let shown: Vec<bool> = todo_list
+        .tasks
+        .iter()
+        .map(|task| todo_list.matches_filter(task))
+        .collect();
+    assert_eq!(shown, [false, false, true, true, false]);
+    assert_eq!(
+        visible(&todo_list),
+        ["[+] write report", due_today.as_str()]
+    );
+    assert!(!todo_list.toggle_today());
+    assert_eq!(todo_list.view_order().len(), 3);
+}
+
+#[test]
+fn headers_cannot_be_selected() {
+    let mut todo_list = list(&["--- Home ---", "[ ] a", "--- Work ---", "[ ] b"]);
+
+    assert_eq!(todo_list.task_index(1), None);
+    assert_eq!(todo_list.task_index(2), Some(1));
+    assert_eq!(todo_list.next_task_row(2, Direction::Down), Some(4));
+    assert_eq!(todo_list.next_task_row(4, Direction::Up), Some(2));
+    assert_eq!(todo_list.next_task_row(2, Direction::Up), None);
+
+    // the header is not a task, so it is neither marked done nor sorted with the tasks
+    todo_list.sort_mode = SortMode::Alphabetical;
+    assert_eq!(todo_list.mark_all(TaskType::Done), 2);
+    assert_eq!(todo_list.tasks[0].text, "--- Home ---");
+    assert_eq!(visible(&todo_list)[0], "--- Home ---");
+
+    // a search shows only matching tasks, without the headers
+    todo_list.filter = "b".to_string();
+    assert_eq!(todo_list.view_order(), [3]);
+}
+
+#[test]
+fn active_first_sinks_finished_tasks_and_keeps_the_file_order() {
+    let mut todo_list = list(&[
+        "[-] e", "[ ] a", "[X] f", "[+] b", "[X] g", "[ ] c", "[-] h", "[+] d",
+    ]);
+    todo_list.sort_mode = SortMode::ActiveFirst;
+
+    assert_eq!(
+        visible(&todo_list),
+        ["[ ] a", "[+] b", "[ ] c", "[+] d", "[-] e", "[X] f", "[X] g", "[-] h"]
+    );
+}
+
+#[test]
+fn deleting_a_task_shifts_the_later_ones_up() {
+    let mut todo_list = list(&["[ ] a", "[ ] b", "[ ] c", "[ ] d"]);
+
+    todo_list.delete_task(1);
+
+    assert_eq!(texts(&todo_list), ["[ ] a", "[ ] c", "[ ] d"]);
+    assert_eq!(todo_list.tasks[1].text, "[ ] c");
+    assert_eq!(todo_list.tasks[2].text, "[ ] d");
+    todo_list.delete_task(5);
+    assert_eq!(todo_list.tasks.len(), 3);
+}
+
+#[test]
+fn a_new_edit_after_undo_and_redo_clears_the_redo_history() {
+    let mut todo_list = list(&["[ ] a", "[ ] b"]);
+
+    todo_list.edit_task(0, "first");
+    todo_list.edit_task(0, "second");
+    assert_eq!(todo_list.undo(), Some(0));
+    assert_eq!(todo_list.tasks[0].text, "[ ] first");
+    assert_eq!(todo_list.undo(), Some(0));
+    assert_eq!(todo_list.redo(), Some(0));
+    assert_eq!(todo_list.tasks[0].text, "[ ] first");
+
+    todo_list.delete_task(1);
+    assert_eq!(todo_list.redo(), None);
+    assert_eq!(texts(&todo_list), ["[ ] first"]);
+    assert_eq!(todo_list.undo(), Some(1));
+    assert_eq!(texts(&todo_list), ["[ ] first", "[ ] b"]);
+}
+
+#[test]
+fn each_sort_mode_orders_a_mixed_list() {
+    let mut todo_list = list(&[
+        "[X] cherry",
+        "[ ] Banana",
+        "[-] apple",
+        "[+] date",
+        "[ ] egg",
+    ]);
+
+    todo_list.sort_mode = SortMode::Status;
+    assert_eq!(
+        visible(&todo_list),
+        [
+            "[ ] Banana",
+            "[ ] egg",
+            "[+] date",
+            "[X] cherry",
+            "[-] apple"
+        ]
+    );
+    todo_list.sort_mode = SortMode::ActiveFirst;
+    assert_eq!(
+        visible(&todo_list),
+        [
+            "[ ] Banana",
+            "[+] date",
+            "[ ] egg",
+            "[X] cherry",
+            "[-] apple"
+        ]
+    );
+    todo_list.sort_mode = SortMode::Alphabetical;
+    assert_eq!(
+        visible(&todo_list),
+        [
+            "[-] apple",
+            "[ ] Banana",
+            "[X] cherry",
+            "[+] date",
+            "[ ] egg"
+        ]
+    );
+    todo_list.sort_mode = SortMode::Insertion;
+    assert_eq!(
+        visible(&todo_list),
+        [
+            "[X] cherry",
+            "[ ] Banana",
+            "[-] apple",
+            "[+] date",
+            "[ ] egg"
+        ]
+    );
+}
+
+#[test]
+fn new_tasks_are_medium_priority_and_low_has_its_own_marker() {
+    let mut todo_list = TodoList::new();
+    todo_list.add(
+        &compose_task_text(TaskType::Todo, Priority::default(), "buy milk"),
+        TaskType::Todo,
+    );
+
+    assert_eq!(todo_list.tasks[0].priority, Priority::Medium);
+    assert_eq!(
+        get_priority_from_string("[ ] legacy line"),
+        Priority::Medium
+    );
+
+    todo_list.set_priority(0, Priority::Low);
+    assert!(todo_list.tasks[0].text.starts_with("[ ] ~ buy milk"));
+    assert_eq!(todo_list.tasks[0].priority, Priority::Low);
+    assert_eq!(
+        get_description(&todo_list.tasks[0].text),
+        "buy milk created:".to_string() + &Date::today().to_string()
+    );
+}
+
+#[test]
+fn only_a_separate_bang_word_is_a_priority_marker() {
+    assert_eq!(get_priority_from_string("[ ] ! call"), Priority::Medium);
+    assert_eq!(get_priority_from_string("[ ] !! call"), Priority::High);
+    assert_eq!(
+        get_priority_from_string("[ ] !important thing"),
+        Priority::default()
+    );
+    assert_eq!(get_description("[ ] !important thing"), "!important thing");
+    assert_eq!(get_description("[ ] !! call"), "call");
+}
+
+#[test]
+fn the_search_ignores_case_in_both_the_filter_and_the_tasks() {
+    let mut todo_list = list(&["[ ] Buy MILK", "[ ] call mom", "[X] milkshake"]);
+    todo_list.sort_mode = SortMode::Insertion;
+
+    todo_list.filter = "mIlK".to_string();
+
+    assert_eq!(visible(&todo_list), ["[ ] Buy MILK", "[X] milkshake"]);
+}
+
+#[test]
+fn prev_undoes_next_for_every_status() {
+    for task_type in [
+        TaskType::Todo,
+        TaskType::Doing,
+        TaskType::Done,
+        TaskType::Rejected,
+        TaskType::NotDefined,
+    ] {
+        assert_eq!(task_type.next().prev(), task_type, "{}", task_type);
+        assert_eq!(task_type.prev().next(), task_type, "{}", task_type);
+    }
+}
+
+#[test]
+fn move_task_swaps_with_the_neighbouring_task() {
+    let mut todo_list = list(&["[ ] a", "[ ] b", "# note", "[ ] c"]);
+
+    assert_eq!(todo_list.move_task(1, Direction::Up), Some(0));
+    assert_eq!(texts(&todo_list), ["[ ] b", "[ ] a", "# note", "[ ] c"]);
+    assert_eq!(todo_list.move_task(1, Direction::Down), Some(3));
+    assert_eq!(texts(&todo_list), ["[ ] b", "[ ] c", "# note", "[ ] a"]);
+    assert_eq!(todo_list.move_task(0, Direction::Up), None);
+    assert_eq!(todo_list.move_task(3, Direction::Down), None);
+    assert_eq!(todo_list.undo(), Some(1));
+    assert_eq!(texts(&todo_list), ["[ ] b", "[ ] a", "# note", "[ ] c"]);
+}
+
+#[test]
+fn a_task_with_several_tags_is_found_by_each_of_them() {
+    let todo_list = list(&[
+        "[ ] buy milk #shop #home",
+        "[ ] fix sink #home",
+        "[X] pay bills #Office, #home.",
+        "[ ] call mom",
+    ]);
+
+    assert_eq!(todo_list.tasks_with_tag("home"), [0, 1, 2]);
+    assert_eq!(todo_list.tasks_with_tag("shop"), [0]);
+    assert_eq!(todo_list.tasks_with_tag("office"), [2]);
+    assert!(todo_list.tasks_with_tag("mom").is_empty());
+}
+
+#[test]
+fn clear_completed_removes_done_and_rejected_tasks_in_one_undo_step() {
+    let mut todo_list = list(&["[X] a", "[ ] b", "[-] c", "[+] d", "[X] e"]);
+
+    assert_eq!(todo_list.clear_completed(), 3);
+    assert_eq!(texts(&todo_list), ["[ ] b", "[+] d"]);
+    assert_eq!(todo_list.clear_completed(), 0);
+
+    todo_list.undo();
+    assert_eq!(
+        texts(&todo_list),
+        ["[X] a", "[ ] b", "[-] c", "[+] d", "[X] e"]
+    );
+}
+
+#[test]
+fn completing_stamps_a_completed_date_and_reopening_clears_it() {
+    let mut todo_list = list(&["[ ] a created:2024-01-01"]);
+    let today = Date::today();
+
+    todo_list.set_type(0, TaskType::Done);
+    assert_eq!(todo_list.tasks[0].completed_at, Some(today));
+    assert_eq!(
+        todo_list.tasks[0].text,
+        format!("[X] a created:2024-01-01 completed:{}", today)
+    );
+
+    todo_list.set_type(0, TaskType::Todo);
+    assert_eq!(todo_list.tasks[0].completed_at, None);
+    assert_eq!(todo_list.tasks[0].text, "[ ] a created:2024-01-01");
+    assert_eq!(todo_list.tasks[0].created_at, Date::parse("2024-01-01"));
+}
+
+#[test]
+fn a_duplicate_goes_right_below_the_original_and_its_subtasks() {
+    let mut todo_list = list(&["[X] a created:2024-01-01", "  [ ] sub", "[ ] b"]);
+    let today = Date::today();
+
+    assert_eq!(todo_list.duplicate_task(0), Some(2));
+    assert_eq!(
+        texts(&todo_list),
+        [
+            "[X] a created:2024-01-01",
+            "[ ] sub",
+            &format!("[ ] a created:{}", today),
+            "[ ] b",
+        ]
+    );
+    assert_eq!(todo_list.tasks[2].task_type, TaskType::Todo);
+
+    assert_eq!(todo_list.duplicate_task(3), Some(4));
+    assert_eq!(todo_list.tasks.len(), 5);
+    assert!(todo_list.tasks[4].text.starts_with("[ ] b"));
+}
+
+#[test]
+fn rejected_tasks_are_left_out_of_the_progress() {
+    let todo_list = list(&["[X] a", "[X] b", "[ ] c", "[-] d", "[-] e", "[-] f"]);
+
+    let counts = todo_list.counts();
+    assert_eq!(counts.percent_done(), 66);
+    assert_eq!(counts.progress_bar(), "[######----] 66%");
+
+    assert_eq!(list(&["[-] a"]).counts().progress_bar(), "[----------] 0%");
+    assert_eq!(
+        list(&["[X] a", "[-] b"]).counts().progress_bar(),
+        "[##########] 100%"
+    );
+}
+
+#[test]
+fn a_parent_moves_up_and_down_with_its_subtasks() {
+    let mut todo_list = list(&[
+        "[ ] a",
+        "  [ ] a1",
+        "# note",
+        "[ ] b",
+        "  [ ] b1",
+        "    [ ] b11",
+        "[ ] c",
+    ]);
+
+    assert_eq!(todo_list.move_task(3, Direction::Up), Some(0));
+    assert_eq!(
+        lines(&todo_list),
+        [
+            "[ ] b",
+            "  [ ] b1",
+            "    [ ] b11",
+            "# note",
+            "[ ] a",
+            "  [ ] a1",
+            "[ ] c",
+        ]
+    );
+    assert_eq!(todo_list.move_task(0, Direction::Down), Some(3));
+    assert_eq!(
+        lines(&todo_list),
+        [
+            "[ ] a",
+            "  [ ] a1",
+            "# note",
+            "[ ] b",
+            "  [ ] b1",
+            "    [ ] b11",
+            "[ ] c",
+        ]
+    );
+    assert_eq!(todo_list.move_task(3, Direction::Down), Some(4));
+    assert_eq!(
+        lines(&todo_list),
+        [
+            "[ ] a",
+            "  [ ] a1",
+            "# note",
+            "[ ] c",
+            "[ ] b",
+            "  [ ] b1",
+            "    [ ] b11",
+        ]
+    );
+    assert_eq!(todo_list.undo(), Some(3));
+    assert_eq!(todo_list.tasks[3].text, "[ ] b");
+    assert_eq!(todo_list.tasks[6].text, "[ ] c");
+}
+
+#[test]
+fn a_subtask_moves_only_among_its_siblings() {
+    let mut todo_list = list(&["[ ] a", "  [ ] a1", "  [ ] a2", "[ ] b", "  [ ] b1"]);
+
+    assert_eq!(todo_list.move_task(1, Direction::Up), None);
+    assert_eq!(todo_list.move_task(2, Direction::Down), None);
+    assert_eq!(todo_list.move_task(4, Direction::Up), None);
+    assert_eq!(todo_list.move_task(1, Direction::Down), Some(2));
+    assert_eq!(
+        lines(&todo_list),
+        ["[ ] a", "  [ ] a2", "  [ ] a1", "[ ] b", "  [ ] b1"]
+    );
+}
+
+#[test]
+fn moving_to_the_top_or_bottom_takes_the_subtasks_along() {
+    let mut todo_list = list(&[
+        "[ ] a", "[ ] b", "  [ ] b1", "[ ] c", "  [ ] c1", "  [ ] c2",
+    ]);
+
+    assert_eq!(todo_list.move_to_top(1), Some(0));
+    assert_eq!(
+        lines(&todo_list),
+        ["[ ] b", "  [ ] b1", "[ ] a", "[ ] c", "  [ ] c1", "  [ ] c2"]
+    );
+    assert_eq!(todo_list.move_to_bottom(0), Some(4));
+    assert_eq!(
+        lines(&todo_list),
+        ["[ ] a", "[ ] c", "  [ ] c1", "  [ ] c2", "[ ] b", "  [ ] b1"]
+    );
+    assert_eq!(todo_list.move_to_top(3), Some(2));
+    assert_eq!(todo_list.move_to_bottom(2), Some(3));
+    assert_eq!(todo_list.move_to_bottom(3), None);
+    assert_eq!(todo_list.undo(), Some(2));
+    assert_eq!(
+        lines(&todo_list),
+        ["[ ] a", "[ ] c", "  [ ] c2", "  [ ] c1", "[ ] b", "  [ ] b1"]
+    );
+}
+
+#[test]
+fn the_subtasks_of_a_removed_task_move_up_a_level() {
+    let mut todo_list = list(&["[ ] a", "  [ ] a1", "    [ ] a11", "[ ] b"]);
+
+    todo_list.delete_task(0);
+    assert_eq!(lines(&todo_list), ["[ ] a1", "  [ ] a11", "[ ] b"]);
+    todo_list.undo();
+    assert_eq!(
+        lines(&todo_list),
+        ["[ ] a", "  [ ] a1", "    [ ] a11", "[ ] b"]
+    );
+
+    let mut todo_list = list(&["[X] a", "  [ ] a1", "  [X] a2", "[-] b", "  [ ] b1"]);
+    assert_eq!(todo_list.clear_completed(), 3);
+    assert_eq!(lines(&todo_list), ["[ ] a1", "[ ] b1"]);
+    todo_list.undo();
+    assert_eq!(
+        lines(&todo_list),
+        ["[X] a", "  [ ] a1", "  [X] a2", "[-] b", "  [ ] b1"]
+    );
+}
+
+#[test]
+fn completing_a_parent_completes_its_subtasks_and_the_last_subtask_its_parent() {
+    let mut todo_list = list(&["[ ] a", "  [ ] a1", "    [ ] a11", "  [ ] a2", "[ ] b"]);
+
+    todo_list.set_type(0, TaskType::Done);
+    assert!(todo_list.tasks[..4]
+        .iter()
+        .all(|task| task.task_type == TaskType::Done));
+    assert_eq!(todo_list.tasks[4].task_type, TaskType::Todo);
+    todo_list.undo();
+    assert!(todo_list
+        .tasks
+        .iter()
+        .all(|task| task.task_type == TaskType::Todo));
+
+    todo_list.set_type(2, TaskType::Done);
+    assert_eq!(todo_list.tasks[1].task_type, TaskType::Done);
+    assert_eq!(todo_list.tasks[0].task_type, TaskType::Todo);
+    todo_list.set_type(3, TaskType::Done);
+    assert_eq!(todo_list.tasks[0].task_type, TaskType::Done);
+    assert_eq!(todo_list.tasks[4].task_type, TaskType::Todo);
+}
+
+#[test]
+fn recurring_tasks_done_before_today_are_open_again() {
+    let mut todo_list = list(&[
+        "[X] water plants @daily completed:2026-03-09",
+        "[X] feed cat @daily completed:2026-03-10",
+        "[X] pay rent completed:2026-03-01",
+        "[-] gym @daily completed:2026-03-01",
+    ]);
+    let today = Date::parse("2026-03-10").unwrap();
+
+    assert_eq!(todo_list.reset_recurring(today), 1);
+    assert_eq!(todo_list.tasks[0].task_type, TaskType::Todo);
+    assert_eq!(todo_list.tasks[0].text, "[ ] water plants @daily");
+    assert_eq!(todo_list.tasks[0].completed_at, None);
+    assert_eq!(todo_list.tasks[1].task_type, TaskType::Done);
+    assert_eq!(todo_list.tasks[2].task_type, TaskType::Done);
+    assert_eq!(todo_list.tasks[3].task_type, TaskType::Rejected);
+
+    let next_day = Date::from_days(today.days_since_epoch() + 1);
+    assert_eq!(todo_list.reset_recurring(next_day), 1);
+    assert_eq!(todo_list.tasks[1].text, "[ ] feed cat @daily");
+}